@@ -0,0 +1,95 @@
+use anchor_lang::prelude::*;
+
+#[error_code]
+pub enum VaultError {
+    #[msg("Vault already exists")]
+    VaultAlreadyExists,
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Withdrawal exceeds vault balance")]
+    InsufficientFunds,
+    #[msg("Withdrawal would leave the vault below the rent-exempt minimum")]
+    DustRemaining,
+    #[msg("Arithmetic overflow")]
+    Overflow,
+    #[msg("Vault is still locked")]
+    StillLocked,
+    #[msg("Unlock time can only be extended")]
+    InvalidUnlockTime,
+    #[msg("Invalid multisig members")]
+    InvalidMembers,
+    #[msg("Invalid multisig threshold")]
+    InvalidThreshold,
+    #[msg("Signer is not a multisig member")]
+    NotMember,
+    #[msg("Member has already approved")]
+    AlreadyApproved,
+    #[msg("Not enough approvals")]
+    NotEnoughApprovals,
+    #[msg("Invalid recipient")]
+    InvalidRecipient,
+    #[msg("Recipient is not on the vault allowlist")]
+    RecipientNotAllowed,
+    #[msg("Too many recipients")]
+    TooManyRecipients,
+    #[msg("Vault label is too long")]
+    LabelTooLong,
+    #[msg("Too many vaults for this owner")]
+    TooManyVaults,
+    #[msg("Withdrawal exceeds the per-epoch limit")]
+    RateLimitExceeded,
+    #[msg("Invalid inactivity period")]
+    InvalidInactivityPeriod,
+    #[msg("Signer is not the vault beneficiary")]
+    NotBeneficiary,
+    #[msg("Owner is still active")]
+    OwnerStillActive,
+    #[msg("Funds are reserved for vesting streams")]
+    FundsReserved,
+    #[msg("Invalid vesting schedule")]
+    InvalidSchedule,
+    #[msg("Amount exceeds the vested balance")]
+    ExceedsVested,
+    #[msg("Vault program is paused")]
+    Paused,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Fee is too high")]
+    FeeTooHigh,
+    #[msg("Invalid treasury")]
+    InvalidTreasury,
+    #[msg("Vault requires a delayed withdrawal")]
+    WithdrawDelayed,
+    #[msg("Withdrawal delay has not passed")]
+    DelayNotElapsed,
+    #[msg("Invalid withdrawal delay")]
+    InvalidDelay,
+    #[msg("Signer is not a recovery guardian")]
+    NotGuardian,
+    #[msg("Invalid subscription period")]
+    InvalidPeriod,
+    #[msg("Subscription charge is not due yet")]
+    NotDue,
+    #[msg("Amount is too small to mint a share")]
+    ZeroShares,
+    #[msg("Not enough shares")]
+    InsufficientShares,
+    #[msg("Missing or invalid Ed25519 signature instruction")]
+    InvalidSignature,
+    #[msg("Invalid nonce")]
+    InvalidNonce,
+    #[msg("Signed withdrawal has expired")]
+    Expired,
+    #[msg("Vault is frozen")]
+    Frozen,
+    #[msg("Too many token mints")]
+    TooManyMints,
+    #[msg("Invalid token account")]
+    InvalidTokenAccount,
+    #[msg("Token balances must be moved as well")]
+    TokensRemaining,
+    #[msg("Vault has an open stake account or pending withdrawal")]
+    VaultInUse,
+    #[msg("Legacy vault ledger does not match the owner")]
+    InvalidLegacyState,
+}
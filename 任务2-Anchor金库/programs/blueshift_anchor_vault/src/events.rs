@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::Discriminator;
+
+/// Emitted via `emit_cpi!` so the event lands in the inner instructions instead of the truncatable logs
+#[event]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultDeposited {
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub amount: u64,
+    pub new_balance: u64,
+    pub slot: u64,
+}
+
+#[event]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultWithdrawn {
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub amount: u64,
+    pub new_balance: u64,
+    pub slot: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VaultEvent {
+    Deposited(VaultDeposited),
+    Withdrawn(VaultWithdrawn),
+}
+
+impl VaultEvent {
+    /// Decode the data of one `emit_cpi!` self-invocation, `None` for any other instruction
+    pub fn decode(ix_data: &[u8]) -> Option<Self> {
+        let data = ix_data.strip_prefix(EVENT_IX_TAG_LE)?;
+        if let Some(mut body) = data.strip_prefix(VaultDeposited::DISCRIMINATOR) {
+            return VaultDeposited::deserialize(&mut body).ok().map(Self::Deposited);
+        }
+        if let Some(mut body) = data.strip_prefix(VaultWithdrawn::DISCRIMINATOR) {
+            return VaultWithdrawn::deserialize(&mut body).ok().map(Self::Withdrawn);
+        }
+        None
+    }
+}
+
+/// Collect the vault events from a transaction's inner instructions,
+/// given as `(program_id, data)` pairs resolved from the transaction metadata
+pub fn decode_events<'a>(
+    inner_instructions: impl IntoIterator<Item = (&'a Pubkey, &'a [u8])>,
+) -> Vec<VaultEvent> {
+    inner_instructions
+        .into_iter()
+        .filter(|(program_id, _)| **program_id == crate::ID)
+        .filter_map(|(_, data)| VaultEvent::decode(data))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::Event;
+
+    fn deposited() -> VaultDeposited {
+        VaultDeposited { owner: Pubkey::new_unique(), vault_id: 3, amount: 500, new_balance: 1_500, slot: 42 }
+    }
+
+    /// Instruction data of the self-invocation `emit_cpi!` makes
+    fn cpi_data(event: &impl Event) -> Vec<u8> {
+        [EVENT_IX_TAG_LE, event.data().as_slice()].concat()
+    }
+
+    #[test]
+    fn decodes_both_events() {
+        let deposit = deposited();
+        assert_eq!(VaultEvent::decode(&cpi_data(&deposit)), Some(VaultEvent::Deposited(deposit)));
+
+        let withdrawal = VaultWithdrawn { owner: Pubkey::new_unique(), vault_id: 0, amount: 7, new_balance: 0, slot: 1 };
+        assert_eq!(VaultEvent::decode(&cpi_data(&withdrawal)), Some(VaultEvent::Withdrawn(withdrawal)));
+    }
+
+    #[test]
+    fn ignores_data_without_the_event_tag() {
+        assert_eq!(VaultEvent::decode(&deposited().data()), None);
+        assert_eq!(VaultEvent::decode(&[]), None);
+    }
+
+    #[test]
+    fn ignores_unknown_or_truncated_events() {
+        let mut data = cpi_data(&deposited());
+        data.truncate(data.len() - 1);
+        assert_eq!(VaultEvent::decode(&data), None);
+        assert_eq!(VaultEvent::decode(&[EVENT_IX_TAG_LE, &[0; 8]].concat()), None);
+    }
+
+    #[test]
+    fn keeps_only_this_program_instructions() {
+        let deposit = deposited();
+        let data = cpi_data(&deposit);
+        let other = Pubkey::new_unique();
+        let events = decode_events([(&other, data.as_slice()), (&crate::ID, data.as_slice())]);
+        assert_eq!(events, vec![VaultEvent::Deposited(deposit)]);
+    }
+}
@@ -0,0 +1,63 @@
+use anchor_lang::prelude::*;
+use crate::state::VaultState;
+use crate::errors::VaultError;
+use crate::events::VaultWithdrawn;
+use super::withdraw::{check_withdrawal, transfer_from_vault};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct Claim<'info> {
+    #[account(mut)]
+    pub beneficiary: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+        has_one = owner,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<Claim>, vault_id: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    // Checked here rather than as a constraint, so a pending beneficiary that is due is applied first
+    let beneficiary = ctx.accounts.vault_state.current_beneficiary(now);
+    require!(beneficiary == Some(ctx.accounts.beneficiary.key()), VaultError::NotBeneficiary);
+    require!(ctx.accounts.vault_state.is_claimable(now), VaultError::OwnerStillActive);
+    require!(!ctx.accounts.vault_state.is_frozen(now), VaultError::Frozen);
+    require!(!ctx.accounts.vault_state.is_locked(now), VaultError::StillLocked);
+
+    let balance = ctx.accounts.vault.lamports();
+    let amount = ctx.accounts.vault_state.spendable(balance, Rent::get()?.minimum_balance(0));
+    check_withdrawal(balance, amount)?;
+
+    // Same `[b"vault", owner, vault_id, bump]` seeds as withdraw, only the destination differs
+    transfer_from_vault(
+        &ctx.accounts.system_program,
+        &ctx.accounts.vault,
+        ctx.accounts.beneficiary.to_account_info(),
+        ctx.accounts.owner.key(),
+        vault_id,
+        ctx.bumps.vault,
+        amount,
+    )?;
+    ctx.accounts.vault_state.record_withdrawal(amount)?;
+
+    emit_cpi!(VaultWithdrawn {
+        owner: ctx.accounts.owner.key(),
+        vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use anchor_lang::prelude::*;
+use crate::program::BlueshiftAnchorVault;
+use crate::state::{Config, MAX_DEPOSIT_FEE_BPS};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = Config::INIT_SPACE + 8,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    // Only the upgrade authority may create the config, otherwise anyone could front-run it
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, BlueshiftAnchorVault>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ VaultError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ VaultError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(new_admin.key()) @ VaultError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn initialize_handler(ctx: Context<InitializeConfig>, deposit_fee_bps: u16, treasury: Pubkey) -> Result<()> {
+    require_gte!(MAX_DEPOSIT_FEE_BPS, deposit_fee_bps, VaultError::FeeTooHigh);
+    ctx.accounts.config.set_inner(Config {
+        admin: ctx.accounts.admin.key(),
+        pending_admin: None,
+        paused: false,
+        deposit_fee_bps,
+        treasury,
+        bump: ctx.bumps.config,
+    });
+    Ok(())
+}
+
+pub fn set_paused_handler(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+    ctx.accounts.config.paused = paused;
+    Ok(())
+}
+
+pub fn set_fee_handler(ctx: Context<UpdateConfig>, deposit_fee_bps: u16, treasury: Pubkey) -> Result<()> {
+    require_gte!(MAX_DEPOSIT_FEE_BPS, deposit_fee_bps, VaultError::FeeTooHigh);
+    let config = &mut ctx.accounts.config;
+    config.deposit_fee_bps = deposit_fee_bps;
+    config.treasury = treasury;
+    Ok(())
+}
+
+pub fn propose_admin_handler(ctx: Context<UpdateConfig>, new_admin: Option<Pubkey>) -> Result<()> {
+    // None withdraws a pending proposal
+    ctx.accounts.config.pending_admin = new_admin;
+    Ok(())
+}
+
+pub fn accept_admin_handler(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    config.admin = ctx.accounts.new_admin.key();
+    config.pending_admin = None;
+    Ok(())
+}
@@ -0,0 +1,149 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, PendingWithdrawal, VaultState};
+use crate::errors::VaultError;
+use crate::events::VaultWithdrawn;
+use super::withdraw::{check_withdrawal, transfer_from_vault};
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct RequestWithdraw<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        init,
+        payer = signer,
+        space = PendingWithdrawal::INIT_SPACE + 8,
+        seeds = [b"pending", vault.key().as_ref()],
+        bump,
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct ExecuteWithdraw<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"pending", vault.key().as_ref()],
+        bump = pending_withdrawal.bump,
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultError::Paused,
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct CancelWithdraw<'info> {
+    /// Owner or guardian
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+    #[account(
+        seeds = [b"vault", owner.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+        has_one = owner,
+        constraint = signer.key() == owner.key()
+            || vault_state.guardian_at(Clock::get()?.unix_timestamp) == Some(signer.key()) @ VaultError::Unauthorized,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"pending", vault.key().as_ref()],
+        bump = pending_withdrawal.bump,
+    )]
+    pub pending_withdrawal: Account<'info, PendingWithdrawal>,
+}
+
+pub fn request_handler(ctx: Context<RequestWithdraw>, vault_id: u64, amount: u64) -> Result<()> {
+    require_gt!(amount, 0, VaultError::InvalidAmount);
+    let now = Clock::get()?.unix_timestamp;
+    let delay = ctx.accounts.vault_state.current_withdraw_delay(now);
+
+    ctx.accounts.pending_withdrawal.set_inner(PendingWithdrawal {
+        owner: ctx.accounts.signer.key(),
+        vault_id,
+        amount,
+        execute_after: now + delay,
+        bump: ctx.bumps.pending_withdrawal,
+    });
+    Ok(())
+}
+
+pub fn execute_handler(ctx: Context<ExecuteWithdraw>, vault_id: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    require_gte!(
+        clock.unix_timestamp,
+        ctx.accounts.pending_withdrawal.execute_after,
+        VaultError::DelayNotElapsed
+    );
+
+    // Everything else is checked at execution time, as for an instant withdrawal
+    let amount = ctx.accounts.pending_withdrawal.amount;
+    let balance = ctx.accounts.vault.lamports();
+    check_withdrawal(balance, amount)?;
+    ctx.accounts.vault_state.check_outflow(&clock, balance, amount)?;
+
+    transfer_from_vault(
+        &ctx.accounts.system_program,
+        &ctx.accounts.vault,
+        ctx.accounts.signer.to_account_info(),
+        ctx.accounts.signer.key(),
+        vault_id,
+        ctx.bumps.vault,
+        amount,
+    )?;
+    ctx.accounts.vault_state.record_withdrawal(amount)?;
+
+    emit_cpi!(VaultWithdrawn {
+        owner: ctx.accounts.signer.key(),
+        vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot: clock.slot,
+    });
+    Ok(())
+}
+
+pub fn cancel_handler(_ctx: Context<CancelWithdraw>, _vault_id: u64) -> Result<()> {
+    Ok(())
+}
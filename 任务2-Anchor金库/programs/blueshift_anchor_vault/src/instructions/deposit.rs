@@ -0,0 +1,103 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::state::{Config, VaultState};
+use crate::errors::VaultError;
+use crate::events::VaultDeposited;
+
+// 前端传递数据的时候，是根据IDL传递的，IDL已经约束了每个账号，mut，singer这些都不需要显式传递，但是实际上已经传了
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct Deposit<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultError::Paused,
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        address = config.treasury @ VaultError::InvalidTreasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Deposit<'info> {
+    /// # Lock the vault until `unlock_at`
+    pub fn extend_lock(&mut self, unlock_at: i64) -> Result<()> {
+        // Shortening the lock would let the owner break the commitment at will
+        require_gte!(unlock_at, self.vault_state.unlock_at, VaultError::InvalidUnlockTime);
+        self.vault_state.unlock_at = unlock_at;
+        Ok(())
+    }
+    /// # Route the protocol fee to the treasury
+    pub fn pay_fee(&self, fee: u64) -> Result<()> {
+        transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.signer.to_account_info(),
+                    to: self.treasury.to_account_info(),
+                },
+            ),
+            fee,
+        )
+    }
+}
+
+pub fn handler(ctx: Context<Deposit>, vault_id: u64, amount: u64, unlock_at: Option<i64>) -> Result<()> {
+    // The fee comes out of `amount`, the vault is credited with the rest
+    let fee = ctx.accounts.config.deposit_fee(amount);
+    let amount = amount - fee;
+
+    // Only the first deposit has to cover the rent-exempt minimum, later ones are top-ups
+    if ctx.accounts.vault.lamports() == 0 {
+        require_gt!(amount, Rent::get()?.minimum_balance(0), VaultError::InvalidAmount);
+    } else {
+        require_gt!(amount, 0, VaultError::InvalidAmount);
+    }
+
+    if fee > 0 {
+        ctx.accounts.pay_fee(fee)?;
+    }
+
+    transfer(
+        CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            Transfer {
+                from: ctx.accounts.signer.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+
+    ctx.accounts.vault_state.record_deposit(amount)?;
+    if let Some(unlock_at) = unlock_at {
+        ctx.accounts.extend_lock(unlock_at)?;
+    }
+
+    emit_cpi!(VaultDeposited {
+        owner: ctx.accounts.signer.key(),
+        vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::{Config, VaultState};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct DepositToken<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    /// Token Accounts
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = signer,
+        associated_token::token_program = token_program
+    )]
+    pub signer_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultError::Paused,
+    )]
+    pub config: Account<'info, Config>,
+    /// Programs
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> DepositToken<'info> {
+    /// # Deposit the tokens
+    pub fn deposit_tokens(&self, amount: u64) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.signer_ata.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.vault_ata.to_account_info(),
+                    authority: self.signer.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint.decimals,
+        )?;
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<DepositToken>, _vault_id: u64, amount: u64) -> Result<()> {
+    require_gt!(amount, 0, VaultError::InvalidAmount);
+    let mint = ctx.accounts.mint.key();
+    ctx.accounts.vault_state.track_mint(mint)?;
+    ctx.accounts.deposit_tokens(amount)?;
+    Ok(())
+}
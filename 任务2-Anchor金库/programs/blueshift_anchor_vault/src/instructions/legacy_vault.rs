@@ -0,0 +1,161 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use anchor_lang::Discriminator;
+use crate::state::VaultState;
+use crate::errors::VaultError;
+use crate::events::VaultDeposited;
+use super::withdraw_token::move_vault_tokens;
+
+// 旧版金库：子金库之前金库地址是 [b"vault", owner]，账本在 [b"state", vault]，字段比现在少。
+// migrate_legacy_vault 把旧金库的代币、lamports 和锁定时间一并搬进所有者已经 open_vault 的子金库，
+// 然后关闭旧账本，租金退还给所有者
+
+/// Length of the legacy ledger written before `unlock_at` existed: discriminator, owner, two totals, bump
+const LEGACY_STATE_NO_LOCK_LEN: usize = 8 + 32 + 8 + 8 + 1;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct MigrateLegacyVault<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", signer.key().as_ref()],
+        bump,
+    )]
+    pub legacy_vault: SystemAccount<'info>,
+    /// CHECK: legacy ledger, parsed by `legacy_unlock_at`. Never created for vaults that predate it
+    #[account(
+        mut,
+        seeds = [b"state", legacy_vault.key().as_ref()],
+        bump,
+    )]
+    pub legacy_state: UncheckedAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+    pub system_program: Program<'info, System>,
+}
+
+/// # Read `unlock_at` from a legacy ledger
+/// Every legacy layout starts with the discriminator, owner and both totals, `unlock_at` follows
+/// in all but the oldest one. An empty account means the vault never had a ledger
+pub fn legacy_unlock_at(data: &[u8], owner: &Pubkey) -> Result<i64> {
+    if data.is_empty() {
+        return Ok(0);
+    }
+    require!(
+        data.len() >= LEGACY_STATE_NO_LOCK_LEN && data.starts_with(VaultState::DISCRIMINATOR),
+        VaultError::InvalidLegacyState
+    );
+    require!(data[8..40] == owner.to_bytes(), VaultError::InvalidLegacyState);
+    if data.len() == LEGACY_STATE_NO_LOCK_LEN {
+        return Ok(0);
+    }
+    Ok(i64::from_le_bytes(data[56..64].try_into().unwrap()))
+}
+
+impl<'info> MigrateLegacyVault<'info> {
+    /// # Move the tokens and lamports into the sub-vault and close the legacy ledger
+    /// Returns the lamports moved
+    pub fn migrate(&mut self, bumps: &MigrateLegacyVaultBumps, token_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        let unlock_at = if self.legacy_state.owner == &crate::ID {
+            legacy_unlock_at(&self.legacy_state.try_borrow_data()?, &self.signer.key())?
+        } else {
+            0
+        };
+        // A migration must not become a way around a time-lock
+        self.vault_state.unlock_at = self.vault_state.unlock_at.max(unlock_at);
+
+        let signer_key = self.signer.key();
+        let signer_seeds: [&[&[u8]]; 1] = [&[b"vault", signer_key.as_ref(), &[bumps.legacy_vault]]];
+        // The legacy ledger never tracked mints, every group passed in is moved
+        move_vault_tokens(
+            token_accounts,
+            &self.legacy_vault.to_account_info(),
+            &signer_seeds,
+            &self.vault.key(),
+            &mut VaultState::default(),
+            &mut self.vault_state,
+        )?;
+
+        let amount = self.legacy_vault.lamports();
+        if amount > 0 {
+            transfer(
+                CpiContext::new_with_signer(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.legacy_vault.to_account_info(),
+                        to: self.vault.to_account_info(),
+                    },
+                    &signer_seeds,
+                ),
+                amount,
+            )?;
+            self.vault_state.record_deposit(amount)?;
+        }
+
+        if self.legacy_state.owner == &crate::ID {
+            let legacy_state = self.legacy_state.to_account_info();
+            **self.signer.to_account_info().try_borrow_mut_lamports()? += legacy_state.lamports();
+            **legacy_state.try_borrow_mut_lamports()? = 0;
+            legacy_state.assign(&System::id());
+            legacy_state.resize(0)?;
+        }
+        Ok(amount)
+    }
+}
+
+pub fn handler<'info>(ctx: Context<'_, '_, 'info, 'info, MigrateLegacyVault<'info>>, vault_id: u64) -> Result<()> {
+    let amount = ctx.accounts.migrate(&ctx.bumps, ctx.remaining_accounts)?;
+
+    emit_cpi!(VaultDeposited {
+        owner: ctx.accounts.signer.key(),
+        vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ledger(owner: &Pubkey, unlock_at: Option<i64>) -> Vec<u8> {
+        let mut data = VaultState::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(owner.as_ref());
+        data.extend_from_slice(&[0; 16]);
+        if let Some(unlock_at) = unlock_at {
+            data.extend_from_slice(&unlock_at.to_le_bytes());
+        }
+        data.push(255);
+        data
+    }
+
+    #[test]
+    fn reads_the_lock_of_each_legacy_layout() {
+        let owner = Pubkey::new_unique();
+        assert_eq!(legacy_unlock_at(&[], &owner).unwrap(), 0);
+        assert_eq!(legacy_unlock_at(&ledger(&owner, None), &owner).unwrap(), 0);
+        assert_eq!(legacy_unlock_at(&ledger(&owner, Some(1_700_000_000)), &owner).unwrap(), 1_700_000_000);
+    }
+
+    #[test]
+    fn rejects_a_ledger_of_another_owner() {
+        let owner = Pubkey::new_unique();
+        assert!(legacy_unlock_at(&ledger(&Pubkey::new_unique(), Some(1)), &owner).is_err());
+        assert!(legacy_unlock_at(&[0; 65], &owner).is_err());
+    }
+}
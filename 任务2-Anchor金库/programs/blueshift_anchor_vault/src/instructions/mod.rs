@@ -0,0 +1,39 @@
+pub mod config;
+pub mod open_vault;
+pub mod legacy_vault;
+pub mod deposit;
+pub mod withdraw;
+pub mod deposit_token;
+pub mod withdraw_token;
+pub mod withdraw_to;
+pub mod withdraw_signed;
+pub mod settings;
+pub mod claim;
+pub mod stream;
+pub mod delayed_withdraw;
+pub mod recovery;
+pub mod transfer_ownership;
+pub mod stake;
+pub mod subscription;
+pub mod multisig;
+pub mod pool;
+
+pub use config::*;
+pub use open_vault::*;
+pub use legacy_vault::*;
+pub use deposit::*;
+pub use withdraw::*;
+pub use deposit_token::*;
+pub use withdraw_token::*;
+pub use withdraw_to::*;
+pub use withdraw_signed::*;
+pub use settings::*;
+pub use claim::*;
+pub use stream::*;
+pub use delayed_withdraw::*;
+pub use recovery::*;
+pub use transfer_ownership::*;
+pub use stake::*;
+pub use subscription::*;
+pub use multisig::*;
+pub use pool::*;
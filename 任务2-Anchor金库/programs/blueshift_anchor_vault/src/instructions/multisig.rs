@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::state::{Multisig, Proposal, MAX_MULTISIG_MEMBERS};
+use crate::errors::VaultError;
+use super::withdraw::check_withdrawal;
+
+// 多签金库：任何人都可以直接用 System Program 向 multisig_vault 转 SOL，
+// 取款必须走 提案(propose) -> 批准(approve) -> 执行(execute) 的流程
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreateMultisig<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = Multisig::INIT_SPACE + 8,
+        seeds = [b"multisig", creator.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+        seeds = [b"multisig_vault", multisig.key().as_ref()],
+        bump,
+    )]
+    pub multisig_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Propose<'info> {
+    #[account(mut)]
+    pub proposer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"multisig", multisig.creator.as_ref(), multisig.seed.to_le_bytes().as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+        init,
+        payer = proposer,
+        space = Proposal::INIT_SPACE + 8,
+        seeds = [b"proposal", multisig.key().as_ref(), multisig.proposal_count.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub proposal: Account<'info, Proposal>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct Approve<'info> {
+    pub member: Signer<'info>,
+    #[account(
+        seeds = [b"multisig", multisig.creator.as_ref(), multisig.seed.to_le_bytes().as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+        mut,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        has_one = multisig,
+    )]
+    pub proposal: Account<'info, Proposal>,
+}
+
+#[derive(Accounts)]
+pub struct Execute<'info> {
+    pub member: Signer<'info>,
+    #[account(
+        seeds = [b"multisig", multisig.creator.as_ref(), multisig.seed.to_le_bytes().as_ref()],
+        bump = multisig.bump,
+    )]
+    pub multisig: Account<'info, Multisig>,
+    #[account(
+        mut,
+        seeds = [b"multisig_vault", multisig.key().as_ref()],
+        bump = multisig.vault_bump,
+    )]
+    pub multisig_vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"proposal", multisig.key().as_ref(), proposal.index.to_le_bytes().as_ref()],
+        bump = proposal.bump,
+        has_one = multisig,
+        has_one = proposer,
+        has_one = recipient @ VaultError::InvalidRecipient,
+    )]
+    pub proposal: Account<'info, Proposal>,
+    /// CHECK: rent refund for the closed proposal, checked by `has_one = proposer`
+    #[account(mut)]
+    pub proposer: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateMultisig<'info> {
+    /// # Create the Multisig
+    pub fn populate_multisig(&mut self, seed: u64, members: Vec<Pubkey>, threshold: u8, bumps: &CreateMultisigBumps) -> Result<()> {
+        self.multisig.set_inner(Multisig {
+            creator: self.creator.key(),
+            seed,
+            members,
+            threshold,
+            proposal_count: 0,
+            bump: bumps.multisig,
+            vault_bump: bumps.multisig_vault,
+        });
+        Ok(())
+    }
+}
+
+impl<'info> Propose<'info> {
+    /// # Create the Proposal, counting the proposer's approval
+    pub fn populate_proposal(&mut self, recipient: Pubkey, amount: u64, bump: u8) -> Result<()> {
+        let member_index = self
+            .multisig
+            .member_index(&self.proposer.key())
+            .ok_or(VaultError::NotMember)?;
+        self.proposal.set_inner(Proposal {
+            multisig: self.multisig.key(),
+            index: self.multisig.proposal_count,
+            proposer: self.proposer.key(),
+            recipient,
+            amount,
+            approvals: 1 << member_index,
+            bump,
+        });
+        self.multisig.proposal_count += 1;
+        Ok(())
+    }
+}
+
+impl<'info> Approve<'info> {
+    pub fn approve(&mut self) -> Result<()> {
+        let member_index = self
+            .multisig
+            .member_index(&self.member.key())
+            .ok_or(VaultError::NotMember)?;
+        let bit = 1 << member_index;
+        require!(self.proposal.approvals & bit == 0, VaultError::AlreadyApproved);
+        self.proposal.approvals |= bit;
+        Ok(())
+    }
+}
+
+impl<'info> Execute<'info> {
+    /// # Pay out the proposal, signed by the multisig vault PDA
+    pub fn execute(&mut self) -> Result<()> {
+        let multisig_key = self.multisig.key();
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"multisig_vault",
+            multisig_key.as_ref(),
+            &[self.multisig.vault_bump],
+        ]];
+
+        transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.multisig_vault.to_account_info(),
+                    to: self.recipient.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            self.proposal.amount,
+        )?;
+        Ok(())
+    }
+}
+
+pub fn create_handler(ctx: Context<CreateMultisig>, seed: u64, members: Vec<Pubkey>, threshold: u8) -> Result<()> {
+    require!(
+        !members.is_empty() && members.len() <= MAX_MULTISIG_MEMBERS,
+        VaultError::InvalidMembers
+    );
+    // Duplicate members would let one key count twice towards the threshold
+    for (i, member) in members.iter().enumerate() {
+        require!(!members[..i].contains(member), VaultError::InvalidMembers);
+    }
+    require!(
+        threshold > 0 && threshold as usize <= members.len(),
+        VaultError::InvalidThreshold
+    );
+
+    ctx.accounts.populate_multisig(seed, members, threshold, &ctx.bumps)?;
+    Ok(())
+}
+
+pub fn propose_handler(ctx: Context<Propose>, recipient: Pubkey, amount: u64) -> Result<()> {
+    require_gt!(amount, 0, VaultError::InvalidAmount);
+    ctx.accounts.populate_proposal(recipient, amount, ctx.bumps.proposal)?;
+    Ok(())
+}
+
+pub fn approve_handler(ctx: Context<Approve>) -> Result<()> {
+    ctx.accounts.approve()?;
+    Ok(())
+}
+
+pub fn execute_handler(ctx: Context<Execute>) -> Result<()> {
+    require!(
+        ctx.accounts.multisig.member_index(&ctx.accounts.member.key()).is_some(),
+        VaultError::NotMember
+    );
+    require_gte!(
+        ctx.accounts.proposal.approval_count(),
+        ctx.accounts.multisig.threshold,
+        VaultError::NotEnoughApprovals
+    );
+    check_withdrawal(ctx.accounts.multisig_vault.lamports(), ctx.accounts.proposal.amount)?;
+
+    ctx.accounts.execute()?;
+    Ok(())
+}
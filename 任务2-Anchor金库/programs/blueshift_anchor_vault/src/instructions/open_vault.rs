@@ -0,0 +1,109 @@
+use anchor_lang::prelude::*;
+use crate::state::{VaultRegistry, VaultState, MAX_LABEL_LEN};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+pub struct OpenVault<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        space = VaultRegistry::INIT_SPACE + 8,
+        seeds = [b"registry", signer.key().as_ref()],
+        bump,
+    )]
+    pub registry: Account<'info, VaultRegistry>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), registry.vault_count.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = signer,
+        space = VaultState::INIT_SPACE + 8,
+        seeds = [b"state", vault.key().as_ref()],
+        bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ListVaults<'info> {
+    #[account(
+        seeds = [b"registry", registry.owner.as_ref()],
+        bump = registry.bump,
+    )]
+    pub registry: Account<'info, VaultRegistry>,
+}
+
+impl<'info> OpenVault<'info> {
+    /// # Create the VaultState and take the next id from the registry
+    pub fn populate_vault_state(&mut self, label: String, bumps: &OpenVaultBumps) -> Result<()> {
+        let vault_id = self.registry.next_vault_id(self.signer.key(), bumps.registry)?;
+
+        self.vault_state.set_inner(VaultState {
+            owner: self.signer.key(),
+            vault_id,
+            label,
+            total_deposited: 0,
+            total_withdrawn: 0,
+            unlock_at: 0,
+            recipients: Vec::new(),
+            pending_recipients: Vec::new(),
+            pending_recipients_at: 0,
+            epoch_limit: 0,
+            pending_epoch_limit: 0,
+            pending_limit_at: 0,
+            withdraw_epoch: 0,
+            epoch_withdrawn: 0,
+            beneficiary: None,
+            inactivity_period: 0,
+            last_heartbeat: 0,
+            pending_beneficiary: None,
+            pending_inactivity_period: 0,
+            pending_beneficiary_at: 0,
+            reserved: 0,
+            withdraw_delay: 0,
+            pending_withdraw_delay: 0,
+            pending_delay_at: 0,
+            guardian: None,
+            pending_guardian: None,
+            pending_guardian_at: 0,
+            recovery_guardians: Vec::new(),
+            recovery_threshold: 0,
+            recovery_delay: 0,
+            recovered_to: None,
+            next_nonce: 0,
+            frozen: false,
+            freeze_cooldown: 0,
+            unfreeze_at: 0,
+            token_mints: Vec::new(),
+            bump: bumps.vault_state,
+        });
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<OpenVault>, label: String) -> Result<()> {
+    require_gte!(MAX_LABEL_LEN, label.len(), VaultError::LabelTooLong);
+    ctx.accounts.populate_vault_state(label, &ctx.bumps)?;
+    Ok(())
+}
+
+pub fn list_handler(ctx: Context<ListVaults>) -> Result<Vec<Pubkey>> {
+    // Capped by MAX_VAULTS_PER_OWNER so the addresses fit in the return data
+    let owner = ctx.accounts.registry.owner;
+    let vaults = (0..ctx.accounts.registry.vault_count)
+        .map(|vault_id| {
+            Pubkey::find_program_address(
+                &[b"vault", owner.as_ref(), vault_id.to_le_bytes().as_ref()],
+                &crate::ID,
+            )
+            .0
+        })
+        .collect();
+    Ok(vaults)
+}
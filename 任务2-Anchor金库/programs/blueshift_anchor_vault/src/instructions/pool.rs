@@ -0,0 +1,201 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::state::{Pool, Position};
+use crate::errors::VaultError;
+
+// 共享金库：成员存入 SOL 换取份额，按份额比例取回。
+// 直接打入 pool_vault 的 lamports（例如质押收益）会提高每一份额的价值
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct CreatePool<'info> {
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    #[account(
+        init,
+        payer = creator,
+        space = Pool::INIT_SPACE + 8,
+        seeds = [b"pool", creator.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        mut,
+        seeds = [b"pool_vault", pool.key().as_ref()],
+        bump,
+    )]
+    pub pool_vault: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PoolDeposit<'info> {
+    #[account(mut)]
+    pub member: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"pool", pool.creator.as_ref(), pool.seed.to_le_bytes().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        mut,
+        seeds = [b"pool_vault", pool.key().as_ref()],
+        bump = pool.vault_bump,
+    )]
+    pub pool_vault: SystemAccount<'info>,
+    #[account(
+        init_if_needed,
+        payer = member,
+        space = Position::INIT_SPACE + 8,
+        seeds = [b"position", pool.key().as_ref(), member.key().as_ref()],
+        bump,
+    )]
+    pub position: Account<'info, Position>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct PoolWithdraw<'info> {
+    #[account(mut)]
+    pub member: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"pool", pool.creator.as_ref(), pool.seed.to_le_bytes().as_ref()],
+        bump = pool.bump,
+    )]
+    pub pool: Account<'info, Pool>,
+    #[account(
+        mut,
+        seeds = [b"pool_vault", pool.key().as_ref()],
+        bump = pool.vault_bump,
+    )]
+    pub pool_vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"position", pool.key().as_ref(), member.key().as_ref()],
+        bump = position.bump,
+        has_one = member,
+    )]
+    pub position: Account<'info, Position>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Lamports backing the shares, the rent-exempt minimum of the pool vault is not part of it
+fn pool_assets(pool_vault: &SystemAccount) -> Result<u64> {
+    Ok(pool_vault.lamports().saturating_sub(Rent::get()?.minimum_balance(0)))
+}
+
+impl<'info> CreatePool<'info> {
+    /// # Create the Pool and make its vault rent-exempt
+    pub fn populate_pool(&mut self, seed: u64, bumps: &CreatePoolBumps) -> Result<()> {
+        self.pool.set_inner(Pool {
+            creator: self.creator.key(),
+            seed,
+            total_shares: 0,
+            bump: bumps.pool,
+            vault_bump: bumps.pool_vault,
+        });
+
+        let rent = Rent::get()?.minimum_balance(0);
+        let missing = rent.saturating_sub(self.pool_vault.lamports());
+        if missing > 0 {
+            transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.creator.to_account_info(),
+                        to: self.pool_vault.to_account_info(),
+                    },
+                ),
+                missing,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'info> PoolDeposit<'info> {
+    pub fn deposit(&mut self, amount: u64, bump: u8) -> Result<()> {
+        // Price the shares before the deposit lands in the vault
+        let shares = self.pool.shares_for(amount, pool_assets(&self.pool_vault)?)?;
+        require_gt!(shares, 0, VaultError::ZeroShares);
+
+        transfer(
+            CpiContext::new(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.member.to_account_info(),
+                    to: self.pool_vault.to_account_info(),
+                },
+            ),
+            amount,
+        )?;
+
+        let position = &mut self.position;
+        // First deposit: the position was just created by init_if_needed
+        if position.member == Pubkey::default() {
+            position.pool = self.pool.key();
+            position.member = self.member.key();
+            position.bump = bump;
+        }
+        position.shares = position.shares.checked_add(shares).ok_or(VaultError::Overflow)?;
+        self.pool.total_shares = self
+            .pool
+            .total_shares
+            .checked_add(shares)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+}
+
+impl<'info> PoolWithdraw<'info> {
+    pub fn withdraw(&mut self, shares: u64) -> Result<()> {
+        let lamports = self.pool.lamports_for(shares, pool_assets(&self.pool_vault)?);
+        require_gt!(lamports, 0, VaultError::InvalidAmount);
+
+        let pool_key = self.pool.key();
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"pool_vault",
+            pool_key.as_ref(),
+            &[self.pool.vault_bump],
+        ]];
+        transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                Transfer {
+                    from: self.pool_vault.to_account_info(),
+                    to: self.member.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            lamports,
+        )?;
+
+        self.position.shares -= shares;
+        self.pool.total_shares -= shares;
+        Ok(())
+    }
+}
+
+pub fn create_handler(ctx: Context<CreatePool>, seed: u64) -> Result<()> {
+    ctx.accounts.populate_pool(seed, &ctx.bumps)?;
+    Ok(())
+}
+
+pub fn deposit_handler(ctx: Context<PoolDeposit>, amount: u64) -> Result<()> {
+    require_gt!(amount, 0, VaultError::InvalidAmount);
+    ctx.accounts.deposit(amount, ctx.bumps.position)?;
+    Ok(())
+}
+
+pub fn withdraw_handler(ctx: Context<PoolWithdraw>, shares: u64) -> Result<()> {
+    require_gt!(shares, 0, VaultError::InvalidAmount);
+    require_gte!(ctx.accounts.position.shares, shares, VaultError::InsufficientShares);
+    ctx.accounts.withdraw(shares)?;
+
+    if ctx.accounts.position.shares == 0 {
+        ctx.accounts.position.close(ctx.accounts.member.to_account_info())?;
+    }
+    Ok(())
+}
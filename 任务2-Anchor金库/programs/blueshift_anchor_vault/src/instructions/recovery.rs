@@ -0,0 +1,233 @@
+use anchor_lang::prelude::*;
+use crate::state::{Recovery, VaultState, MIN_RECOVERY_DELAY};
+use crate::errors::VaultError;
+use crate::events::{VaultDeposited, VaultWithdrawn};
+use super::withdraw::{check_withdrawal, transfer_from_vault};
+use super::withdraw_token::move_vault_tokens;
+
+// 社交恢复：金库地址由所有者公钥派生，私钥丢失后只能由守护者多数同意，
+// 把余额迁移到新所有者的金库 PDA。此时权限证明不再是所有者签名，而是 Recovery 账户上的批准数
+// 每个新所有者各有一份提案，单个守护者提出的恶意提案挡不住其他提案，所有者或提案人可以撤销它
+// 提案创建后要等 recovery_delay 才能执行，所有者还持有私钥时有时间撤销
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64, new_owner: Pubkey)]
+pub struct ProposeRecovery<'info> {
+    #[account(mut)]
+    pub guardian: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    #[account(
+        seeds = [b"vault", owner.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+        has_one = owner,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        init,
+        payer = guardian,
+        space = Recovery::INIT_SPACE + 8,
+        seeds = [b"recovery", vault.key().as_ref(), new_owner.as_ref()],
+        bump,
+    )]
+    pub recovery: Account<'info, Recovery>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ApproveRecovery<'info> {
+    pub guardian: Signer<'info>,
+    #[account(
+        seeds = [b"vault", recovery.owner.as_ref(), recovery.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [b"recovery", vault.key().as_ref(), recovery.new_owner.as_ref()],
+        bump = recovery.bump,
+    )]
+    pub recovery: Account<'info, Recovery>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteRecovery<'info> {
+    pub guardian: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", recovery.owner.as_ref(), recovery.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+    #[account(
+        mut,
+        seeds = [b"vault", recovery.new_owner.as_ref(), recovery.new_vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub new_vault: SystemAccount<'info>,
+    /// The new owner has to open the target vault first
+    #[account(
+        mut,
+        seeds = [b"state", new_vault.key().as_ref()],
+        bump = new_vault_state.bump,
+    )]
+    pub new_vault_state: Box<Account<'info, VaultState>>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"recovery", vault.key().as_ref(), recovery.new_owner.as_ref()],
+        bump = recovery.bump,
+        has_one = proposer,
+    )]
+    pub recovery: Account<'info, Recovery>,
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CancelRecovery<'info> {
+    #[account(
+        constraint = signer.key() == recovery.owner || signer.key() == recovery.proposer @ VaultError::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", recovery.owner.as_ref(), recovery.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = proposer,
+        seeds = [b"recovery", vault.key().as_ref(), recovery.new_owner.as_ref()],
+        bump = recovery.bump,
+        has_one = proposer,
+    )]
+    pub recovery: Account<'info, Recovery>,
+    #[account(mut)]
+    pub proposer: SystemAccount<'info>,
+}
+
+impl<'info> ExecuteRecovery<'info> {
+    /// # Move the tokens and the spendable balance to the new owner's vault
+    /// Returns the lamports moved
+    pub fn migrate(&mut self, bump: u8, token_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        // A frozen vault can still be recovered, moving to a fresh key is the way out of a compromise
+        let vault_id = self.recovery.vault_id.to_le_bytes();
+        let signer_seeds: [&[&[u8]]; 1] = [&[b"vault", self.recovery.owner.as_ref(), vault_id.as_ref(), &[bump]]];
+        move_vault_tokens(
+            token_accounts,
+            &self.vault.to_account_info(),
+            &signer_seeds,
+            &self.new_vault.key(),
+            &mut self.vault_state,
+            &mut self.new_vault_state,
+        )?;
+
+        let balance = self.vault.lamports();
+        let amount = self.vault_state.spendable(balance, Rent::get()?.minimum_balance(0));
+        check_withdrawal(balance, amount)?;
+
+        transfer_from_vault(
+            &self.system_program,
+            &self.vault,
+            self.new_vault.to_account_info(),
+            self.recovery.owner,
+            self.recovery.vault_id,
+            bump,
+            amount,
+        )?;
+        self.vault_state.record_withdrawal(amount)?;
+        self.new_vault_state.record_deposit(amount)?;
+        // A stake account left behind can still be deactivated and withdrawn to the new vault
+        self.vault_state.recovered_to = Some(self.new_vault.key());
+        // Recovery must not become a way around a time-lock
+        self.new_vault_state.unlock_at = self.new_vault_state.unlock_at.max(self.vault_state.unlock_at);
+        Ok(amount)
+    }
+}
+
+pub fn propose_handler(ctx: Context<ProposeRecovery>, vault_id: u64, new_owner: Pubkey, new_vault_id: u64) -> Result<()> {
+    require!(
+        ctx.accounts.vault_state.is_guardian(&ctx.accounts.guardian.key()),
+        VaultError::NotGuardian
+    );
+
+    ctx.accounts.recovery.set_inner(Recovery {
+        owner: ctx.accounts.owner.key(),
+        vault_id,
+        new_owner,
+        new_vault_id,
+        proposer: ctx.accounts.guardian.key(),
+        approvers: vec![ctx.accounts.guardian.key()],
+        created_at: Clock::get()?.unix_timestamp,
+        bump: ctx.bumps.recovery,
+    });
+    Ok(())
+}
+
+pub fn approve_handler(ctx: Context<ApproveRecovery>) -> Result<()> {
+    let guardian = ctx.accounts.guardian.key();
+    require!(ctx.accounts.vault_state.is_guardian(&guardian), VaultError::NotGuardian);
+    let recovery = &mut ctx.accounts.recovery;
+    require!(!recovery.approvers.contains(&guardian), VaultError::AlreadyApproved);
+    recovery.approvers.push(guardian);
+    Ok(())
+}
+
+pub fn execute_handler<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteRecovery<'info>>) -> Result<()> {
+    let vault_state = &ctx.accounts.vault_state;
+    require!(vault_state.is_guardian(&ctx.accounts.guardian.key()), VaultError::NotGuardian);
+    require!(vault_state.recovery_threshold > 0, VaultError::NotEnoughApprovals);
+    require_gte!(
+        ctx.accounts.recovery.approval_count(&vault_state.recovery_guardians),
+        vault_state.recovery_threshold,
+        VaultError::NotEnoughApprovals
+    );
+    // Gives the owner time to cancel a recovery they did not ask for
+    let delay = vault_state.recovery_delay.max(MIN_RECOVERY_DELAY);
+    require_gte!(
+        Clock::get()?.unix_timestamp,
+        ctx.accounts.recovery.created_at.saturating_add(delay),
+        VaultError::DelayNotElapsed
+    );
+
+    let amount = ctx.accounts.migrate(ctx.bumps.vault, ctx.remaining_accounts)?;
+
+    let slot = Clock::get()?.slot;
+    emit_cpi!(VaultWithdrawn {
+        owner: ctx.accounts.recovery.owner,
+        vault_id: ctx.accounts.recovery.vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot,
+    });
+    emit_cpi!(VaultDeposited {
+        owner: ctx.accounts.recovery.new_owner,
+        vault_id: ctx.accounts.recovery.new_vault_id,
+        amount,
+        new_balance: ctx.accounts.new_vault.lamports(),
+        slot,
+    });
+    Ok(())
+}
+
+pub fn cancel_handler(_ctx: Context<CancelRecovery>) -> Result<()> {
+    Ok(())
+}
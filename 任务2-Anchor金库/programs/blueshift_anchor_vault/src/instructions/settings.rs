@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+use crate::state::{VaultState, MAX_RECIPIENTS, MAX_RECOVERY_GUARDIANS, MIN_FREEZE_COOLDOWN, MIN_INACTIVITY_PERIOD, MIN_RECOVERY_DELAY};
+use crate::errors::VaultError;
+
+/// Owner-only settings on an opened vault
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct VaultSettings<'info> {
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+}
+
+pub fn set_recipients_handler(ctx: Context<VaultSettings>, _vault_id: u64, recipients: Vec<Pubkey>) -> Result<()> {
+    require_gte!(MAX_RECIPIENTS, recipients.len(), VaultError::TooManyRecipients);
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault_state.set_recipients(recipients, now);
+    Ok(())
+}
+
+pub fn set_beneficiary_handler(
+    ctx: Context<VaultSettings>,
+    _vault_id: u64,
+    beneficiary: Option<Pubkey>,
+    inactivity_period: i64,
+) -> Result<()> {
+    if beneficiary.is_some() {
+        require_gte!(inactivity_period, MIN_INACTIVITY_PERIOD, VaultError::InvalidInactivityPeriod);
+    }
+    let now = Clock::get()?.unix_timestamp;
+    let state = &mut ctx.accounts.vault_state;
+    state.set_beneficiary(beneficiary, inactivity_period, now);
+    state.last_heartbeat = now;
+    Ok(())
+}
+
+pub fn heartbeat_handler(ctx: Context<VaultSettings>, _vault_id: u64) -> Result<()> {
+    ctx.accounts.vault_state.last_heartbeat = Clock::get()?.unix_timestamp;
+    Ok(())
+}
+
+pub fn set_withdraw_delay_handler(
+    ctx: Context<VaultSettings>,
+    _vault_id: u64,
+    withdraw_delay: i64,
+    guardian: Option<Pubkey>,
+) -> Result<()> {
+    require_gte!(withdraw_delay, 0, VaultError::InvalidDelay);
+    let now = Clock::get()?.unix_timestamp;
+    let state = &mut ctx.accounts.vault_state;
+    // Guardian first, so a removal waits out the delay in force before this call
+    state.set_guardian(guardian, now);
+    state.set_withdraw_delay(withdraw_delay, now);
+    Ok(())
+}
+
+pub fn set_recovery_guardians_handler(
+    ctx: Context<VaultSettings>,
+    _vault_id: u64,
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    recovery_delay: i64,
+) -> Result<()> {
+    // An empty list turns recovery off
+    require_gte!(MAX_RECOVERY_GUARDIANS, guardians.len(), VaultError::InvalidMembers);
+    for (i, guardian) in guardians.iter().enumerate() {
+        require!(!guardians[..i].contains(guardian), VaultError::InvalidMembers);
+    }
+    if guardians.is_empty() {
+        require_eq!(threshold, 0, VaultError::InvalidThreshold);
+    } else {
+        require!(
+            threshold > 0 && threshold as usize <= guardians.len(),
+            VaultError::InvalidThreshold
+        );
+        require_gte!(recovery_delay, MIN_RECOVERY_DELAY, VaultError::InvalidDelay);
+    }
+
+    let state = &mut ctx.accounts.vault_state;
+    state.recovery_guardians = guardians;
+    state.recovery_threshold = threshold;
+    state.recovery_delay = recovery_delay;
+    Ok(())
+}
+
+pub fn set_withdraw_limit_handler(ctx: Context<VaultSettings>, _vault_id: u64, epoch_limit: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    ctx.accounts.vault_state.set_epoch_limit(epoch_limit, now);
+    Ok(())
+}
+
+pub fn set_freeze_cooldown_handler(ctx: Context<VaultSettings>, _vault_id: u64, freeze_cooldown: i64) -> Result<()> {
+    require_gte!(freeze_cooldown, MIN_FREEZE_COOLDOWN, VaultError::InvalidDelay);
+    let state = &mut ctx.accounts.vault_state;
+    // Otherwise a stolen key could shorten the cooldown of a frozen vault
+    require!(!state.is_frozen(Clock::get()?.unix_timestamp), VaultError::Frozen);
+    state.freeze_cooldown = freeze_cooldown;
+    Ok(())
+}
+
+pub fn freeze_handler(ctx: Context<VaultSettings>, _vault_id: u64) -> Result<()> {
+    // Freezing again also drops an unfreeze that is still cooling down
+    let state = &mut ctx.accounts.vault_state;
+    state.frozen = true;
+    state.unfreeze_at = 0;
+    Ok(())
+}
+
+pub fn unfreeze_handler(ctx: Context<VaultSettings>, _vault_id: u64) -> Result<()> {
+    let state = &mut ctx.accounts.vault_state;
+    if state.frozen && state.unfreeze_at == 0 {
+        // Vaults that never set a cooldown still wait the minimum
+        let cooldown = state.freeze_cooldown.max(MIN_FREEZE_COOLDOWN);
+        state.unfreeze_at = Clock::get()?.unix_timestamp + cooldown;
+    }
+    Ok(())
+}
@@ -0,0 +1,281 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::sysvar::stake_history;
+use anchor_lang::system_program::{allocate, assign, create_account, transfer, Allocate, Assign, CreateAccount, Transfer};
+use solana_stake_interface::instruction as stake_instruction;
+use solana_stake_interface::program::ID as STAKE_PROGRAM_ID;
+use solana_stake_interface::state::{Authorized, Lockup, StakeStateV2};
+use crate::state::VaultState;
+use crate::errors::VaultError;
+use super::withdraw::check_withdrawal;
+
+// 质押：由金库 PDA 创建并委托一个原生质押账户，staker 和 withdrawer 都是金库 PDA，
+// 所有 CPI 都用金库的 [b"vault", owner, vault_id, bump] 种子签名。
+// 取回质押不需要所有者签名，资金只会回到金库；金库被恢复之后，守护者也能解除委托并把质押取到新金库
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct CreateStake<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    /// CHECK: created here as a stake account, one per vault. May already hold lamports sent by anyone
+    #[account(
+        mut,
+        seeds = [b"stake", vault.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake program
+    pub vote_account: UncheckedAccount<'info>,
+    /// CHECK: deprecated stake config, passed through to the stake program
+    pub stake_config: UncheckedAccount<'info>,
+    /// CHECK: stake history sysvar
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    /// CHECK: stake program
+    #[account(address = STAKE_PROGRAM_ID)]
+    pub stake_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct DeactivateStake<'info> {
+    /// Owner, or anyone once the vault has been recovered
+    #[account(
+        constraint = signer.key() == owner.key() || vault_state.recovered_to.is_some() @ VaultError::Unauthorized,
+    )]
+    pub signer: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    #[account(
+        seeds = [b"vault", owner.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+        has_one = owner,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    /// CHECK: owned by the stake program, which checks the vault is its staker
+    #[account(
+        mut,
+        seeds = [b"stake", vault.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: stake program
+    #[account(address = STAKE_PROGRAM_ID)]
+    pub stake_program: UncheckedAccount<'info>,
+}
+
+/// Permissionless, the lamports can only go back to the vault or the vault it was recovered to
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct WithdrawStake<'info> {
+    pub owner: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+        has_one = owner,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        address = vault_state.recovered_to.unwrap_or(vault.key()) @ VaultError::InvalidRecipient,
+    )]
+    pub destination: SystemAccount<'info>,
+    /// CHECK: owned by the stake program, which checks the vault is its withdrawer
+    #[account(
+        mut,
+        seeds = [b"stake", vault.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: UncheckedAccount<'info>,
+    /// CHECK: stake history sysvar
+    #[account(address = stake_history::ID)]
+    pub stake_history: UncheckedAccount<'info>,
+    pub clock: Sysvar<'info, Clock>,
+    /// CHECK: stake program
+    #[account(address = STAKE_PROGRAM_ID)]
+    pub stake_program: UncheckedAccount<'info>,
+}
+
+impl<'info> CreateStake<'info> {
+    /// # Fund the stake account from the vault and initialize it with the vault as authority
+    /// `create_account` fails on an address that already holds lamports, so a pre-funded
+    /// stake PDA is topped up, allocated and assigned instead
+    pub fn create_stake_account(&self, vault_id: u64, amount: u64, bumps: &CreateStakeBumps) -> Result<()> {
+        let signer_key = self.signer.key();
+        let vault_key = self.vault.key();
+        let vault_id = vault_id.to_le_bytes();
+        let signer_seeds: [&[&[u8]]; 2] = [
+            &[b"vault", signer_key.as_ref(), vault_id.as_ref(), &[bumps.vault]],
+            &[b"stake", vault_key.as_ref(), &[bumps.stake_account]],
+        ];
+
+        if self.stake_account.lamports() == 0 {
+            create_account(
+                CpiContext::new_with_signer(
+                    self.system_program.to_account_info(),
+                    CreateAccount {
+                        from: self.vault.to_account_info(),
+                        to: self.stake_account.to_account_info(),
+                    },
+                    &signer_seeds,
+                ),
+                amount,
+                StakeStateV2::size_of() as u64,
+                &STAKE_PROGRAM_ID,
+            )?;
+        } else {
+            transfer(
+                CpiContext::new_with_signer(
+                    self.system_program.to_account_info(),
+                    Transfer {
+                        from: self.vault.to_account_info(),
+                        to: self.stake_account.to_account_info(),
+                    },
+                    &signer_seeds,
+                ),
+                amount,
+            )?;
+            allocate(
+                CpiContext::new_with_signer(
+                    self.system_program.to_account_info(),
+                    Allocate {
+                        account_to_allocate: self.stake_account.to_account_info(),
+                    },
+                    &signer_seeds,
+                ),
+                StakeStateV2::size_of() as u64,
+            )?;
+            assign(
+                CpiContext::new_with_signer(
+                    self.system_program.to_account_info(),
+                    Assign {
+                        account_to_assign: self.stake_account.to_account_info(),
+                    },
+                    &signer_seeds,
+                ),
+                &STAKE_PROGRAM_ID,
+            )?;
+        }
+
+        invoke(
+            &stake_instruction::initialize(
+                &self.stake_account.key(),
+                &Authorized {
+                    staker: vault_key,
+                    withdrawer: vault_key,
+                },
+                &Lockup::default(),
+            ),
+            &[self.stake_account.to_account_info(), self.rent.to_account_info()],
+        )?;
+        Ok(())
+    }
+    /// # Delegate to the chosen validator
+    pub fn delegate(&self, vault_id: u64, bump: u8) -> Result<()> {
+        let signer_key = self.signer.key();
+        let vault_id = vault_id.to_le_bytes();
+        let signer_seeds: [&[&[u8]]; 1] = [&[b"vault", signer_key.as_ref(), vault_id.as_ref(), &[bump]]];
+
+        invoke_signed(
+            &stake_instruction::delegate_stake(
+                &self.stake_account.key(),
+                &self.vault.key(),
+                &self.vote_account.key(),
+            ),
+            &[
+                self.stake_account.to_account_info(),
+                self.vote_account.to_account_info(),
+                self.clock.to_account_info(),
+                self.stake_history.to_account_info(),
+                self.stake_config.to_account_info(),
+                self.vault.to_account_info(),
+            ],
+            &signer_seeds,
+        )?;
+        Ok(())
+    }
+}
+
+pub fn create_handler(ctx: Context<CreateStake>, vault_id: u64, amount: u64) -> Result<()> {
+    require!(
+        !ctx.accounts.vault_state.is_frozen(ctx.accounts.clock.unix_timestamp),
+        VaultError::Frozen
+    );
+    let balance = ctx.accounts.vault.lamports();
+    let rent = Rent::get()?.minimum_balance(0);
+    require_gte!(ctx.accounts.vault_state.spendable(balance, rent), amount, VaultError::FundsReserved);
+    check_withdrawal(balance, amount)?;
+
+    ctx.accounts.create_stake_account(vault_id, amount, &ctx.bumps)?;
+    ctx.accounts.delegate(vault_id, ctx.bumps.vault)?;
+    Ok(())
+}
+
+pub fn deactivate_handler(ctx: Context<DeactivateStake>, vault_id: u64) -> Result<()> {
+    let owner_key = ctx.accounts.owner.key();
+    let vault_id = vault_id.to_le_bytes();
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"vault", owner_key.as_ref(), vault_id.as_ref(), &[ctx.bumps.vault]]];
+
+    invoke_signed(
+        &stake_instruction::deactivate_stake(&ctx.accounts.stake_account.key(), &ctx.accounts.vault.key()),
+        &[
+            ctx.accounts.stake_account.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+        ],
+        &signer_seeds,
+    )?;
+    Ok(())
+}
+
+pub fn withdraw_handler(ctx: Context<WithdrawStake>, vault_id: u64) -> Result<()> {
+    let owner_key = ctx.accounts.owner.key();
+    let vault_id = vault_id.to_le_bytes();
+    let signer_seeds: [&[&[u8]]; 1] = [&[b"vault", owner_key.as_ref(), vault_id.as_ref(), &[ctx.bumps.vault]]];
+
+    // Withdrawing everything, principal plus rewards, also closes the stake account
+    invoke_signed(
+        &stake_instruction::withdraw(
+            &ctx.accounts.stake_account.key(),
+            &ctx.accounts.vault.key(),
+            &ctx.accounts.destination.key(),
+            ctx.accounts.stake_account.lamports(),
+            None,
+        ),
+        &[
+            ctx.accounts.stake_account.to_account_info(),
+            ctx.accounts.destination.to_account_info(),
+            ctx.accounts.clock.to_account_info(),
+            ctx.accounts.stake_history.to_account_info(),
+            ctx.accounts.vault.to_account_info(),
+        ],
+        &signer_seeds,
+    )?;
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use anchor_lang::prelude::*;
+use crate::state::{Stream, VaultState};
+use crate::errors::VaultError;
+use crate::events::VaultWithdrawn;
+use super::withdraw::{check_withdrawal, transfer_from_vault};
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64, recipient: Pubkey)]
+pub struct CreateStream<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        init,
+        payer = signer,
+        space = Stream::INIT_SPACE + 8,
+        seeds = [b"stream", vault.key().as_ref(), recipient.as_ref()],
+        bump,
+    )]
+    pub stream: Account<'info, Stream>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawVested<'info> {
+    #[account(mut)]
+    pub recipient: Signer<'info>,
+    /// Gets the stream rent back once it is fully paid out
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref(), stream.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+        has_one = owner,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [b"stream", vault.key().as_ref(), recipient.key().as_ref()],
+        bump = stream.bump,
+        has_one = owner,
+        has_one = recipient,
+    )]
+    pub stream: Account<'info, Stream>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateStream<'info> {
+    /// # Create the Stream and reserve its total in the vault
+    #[allow(clippy::too_many_arguments)]
+    pub fn populate_stream(&mut self, vault_id: u64, recipient: Pubkey, start: i64, cliff: i64, end: i64, total: u64, bump: u8) -> Result<()> {
+        // Same floor as `VaultState::spendable` once the stream is open
+        let rent = Rent::get()?.minimum_balance(0);
+        let free = self
+            .vault_state
+            .unreserved(self.vault.lamports())
+            .saturating_sub(rent);
+        require_gte!(free, total, VaultError::InsufficientFunds);
+
+        self.vault_state.reserved = self
+            .vault_state
+            .reserved
+            .checked_add(total)
+            .ok_or(VaultError::Overflow)?;
+        self.stream.set_inner(Stream {
+            owner: self.signer.key(),
+            vault_id,
+            recipient,
+            start,
+            cliff,
+            end,
+            total,
+            withdrawn: 0,
+            bump,
+        });
+        Ok(())
+    }
+}
+
+pub fn create_handler(
+    ctx: Context<CreateStream>,
+    vault_id: u64,
+    recipient: Pubkey,
+    start: i64,
+    cliff: i64,
+    end: i64,
+    total: u64,
+) -> Result<()> {
+    require_gt!(total, 0, VaultError::InvalidAmount);
+    require!(start < end && start <= cliff && cliff <= end, VaultError::InvalidSchedule);
+
+    // A stream is an outflow to `recipient`, so it obeys the same rules as `withdraw_to`:
+    // nothing is paid out sooner than a delayed withdrawal could be
+    let now = Clock::get()?.unix_timestamp;
+    let vault_state = &mut ctx.accounts.vault_state;
+    require!(!vault_state.is_frozen(now), VaultError::Frozen);
+    require!(!vault_state.is_locked(now), VaultError::StillLocked);
+    require!(vault_state.allows_recipient(&recipient, now), VaultError::RecipientNotAllowed);
+    let delay = vault_state.current_withdraw_delay(now);
+    require!(start >= now && end - start >= delay, VaultError::InvalidSchedule);
+
+    ctx.accounts.populate_stream(vault_id, recipient, start, cliff, end, total, ctx.bumps.stream)?;
+    Ok(())
+}
+
+pub fn withdraw_handler(ctx: Context<WithdrawVested>, amount: u64) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    require!(!ctx.accounts.vault_state.is_frozen(now), VaultError::Frozen);
+    require_gte!(ctx.accounts.stream.withdrawable(now), amount, VaultError::ExceedsVested);
+    check_withdrawal(ctx.accounts.vault.lamports(), amount)?;
+    ctx.accounts.vault_state.record_epoch_outflow(&Clock::get()?, amount)?;
+
+    let vault_id = ctx.accounts.stream.vault_id;
+    transfer_from_vault(
+        &ctx.accounts.system_program,
+        &ctx.accounts.vault,
+        ctx.accounts.recipient.to_account_info(),
+        ctx.accounts.owner.key(),
+        vault_id,
+        ctx.bumps.vault,
+        amount,
+    )?;
+
+    let stream = &mut ctx.accounts.stream;
+    stream.withdrawn += amount;
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.reserved -= amount;
+    vault_state.record_withdrawal(amount)?;
+
+    emit_cpi!(VaultWithdrawn {
+        owner: ctx.accounts.owner.key(),
+        vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot: Clock::get()?.slot,
+    });
+
+    if ctx.accounts.stream.withdrawn == ctx.accounts.stream.total {
+        ctx.accounts.stream.close(ctx.accounts.owner.to_account_info())?;
+    }
+    Ok(())
+}
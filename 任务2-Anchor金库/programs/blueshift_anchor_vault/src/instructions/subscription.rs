@@ -0,0 +1,151 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, Subscription, VaultState};
+use crate::errors::VaultError;
+use crate::events::VaultWithdrawn;
+use super::withdraw::{check_withdrawal, transfer_from_vault};
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64, merchant: Pubkey)]
+pub struct AuthorizeSubscription<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        init,
+        payer = signer,
+        space = Subscription::INIT_SPACE + 8,
+        seeds = [b"subscription", vault.key().as_ref(), merchant.as_ref()],
+        bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Permissionless, anyone can crank a due charge
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Charge<'info> {
+    pub owner: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref(), subscription.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+        has_one = owner,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        mut,
+        seeds = [b"subscription", vault.key().as_ref(), merchant.key().as_ref()],
+        bump = subscription.bump,
+        has_one = owner,
+        has_one = merchant,
+    )]
+    pub subscription: Account<'info, Subscription>,
+    #[account(mut)]
+    pub merchant: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultError::Paused,
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64, merchant: Pubkey)]
+pub struct CancelSubscription<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"subscription", vault.key().as_ref(), merchant.as_ref()],
+        bump = subscription.bump,
+    )]
+    pub subscription: Account<'info, Subscription>,
+}
+
+pub fn authorize_handler(
+    ctx: Context<AuthorizeSubscription>,
+    vault_id: u64,
+    merchant: Pubkey,
+    amount: u64,
+    period: i64,
+) -> Result<()> {
+    require_gt!(amount, 0, VaultError::InvalidAmount);
+    require_gt!(period, 0, VaultError::InvalidPeriod);
+    // A subscription is a standing withdrawal, it must not get around the allowlist or the delay
+    let vault_state = &mut ctx.accounts.vault_state;
+    let now = Clock::get()?.unix_timestamp;
+    require!(vault_state.allows_recipient(&merchant, now), VaultError::RecipientNotAllowed);
+    let first_due = now + vault_state.current_withdraw_delay(now);
+
+    ctx.accounts.subscription.set_inner(Subscription {
+        owner: ctx.accounts.signer.key(),
+        vault_id,
+        merchant,
+        amount,
+        period,
+        next_due: first_due,
+        bump: ctx.bumps.subscription,
+    });
+    Ok(())
+}
+
+pub fn charge_handler(ctx: Context<Charge>) -> Result<()> {
+    let clock = Clock::get()?;
+    require_gte!(clock.unix_timestamp, ctx.accounts.subscription.next_due, VaultError::NotDue);
+
+    let amount = ctx.accounts.subscription.amount;
+    let balance = ctx.accounts.vault.lamports();
+    check_withdrawal(balance, amount)?;
+    ctx.accounts.vault_state.check_outflow(&clock, balance, amount)?;
+
+    let vault_id = ctx.accounts.subscription.vault_id;
+    transfer_from_vault(
+        &ctx.accounts.system_program,
+        &ctx.accounts.vault,
+        ctx.accounts.merchant.to_account_info(),
+        ctx.accounts.owner.key(),
+        vault_id,
+        ctx.bumps.vault,
+        amount,
+    )?;
+    ctx.accounts.vault_state.record_withdrawal(amount)?;
+    ctx.accounts.subscription.advance(clock.unix_timestamp);
+
+    emit_cpi!(VaultWithdrawn {
+        owner: ctx.accounts.owner.key(),
+        vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot: clock.slot,
+    });
+    Ok(())
+}
+
+pub fn cancel_handler(_ctx: Context<CancelSubscription>, _vault_id: u64, _merchant: Pubkey) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,230 @@
+use anchor_lang::prelude::*;
+use solana_stake_interface::program::ID as STAKE_PROGRAM_ID;
+use crate::state::{Config, OwnershipTransfer, VaultRegistry, VaultState};
+use crate::errors::VaultError;
+use crate::events::{VaultDeposited, VaultWithdrawn};
+use super::withdraw::transfer_from_vault;
+use super::withdraw_token::move_vault_tokens;
+
+// 所有权转移：金库地址由所有者公钥派生，无法直接改 owner，
+// 只能把余额和 VaultState 搬到新所有者名下新开的子金库。两步完成，新所有者签名接受后才会搬迁，防止转错地址
+// 代币余额通过 remaining_accounts 一并搬走；质押账户和待执行的延迟取款同样挂在旧地址上，需先结清
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct ProposeTransfer<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        init,
+        payer = signer,
+        space = OwnershipTransfer::INIT_SPACE + 8,
+        seeds = [b"transfer", vault.key().as_ref()],
+        bump,
+    )]
+    pub transfer: Account<'info, OwnershipTransfer>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptTransfer<'info> {
+    #[account(mut)]
+    pub new_owner: Signer<'info>,
+    /// Receives the rent of the closed accounts
+    #[account(mut)]
+    pub owner: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref(), transfer.vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+        has_one = owner,
+    )]
+    pub vault_state: Box<Account<'info, VaultState>>,
+    #[account(
+        mut,
+        close = owner,
+        seeds = [b"transfer", vault.key().as_ref()],
+        bump = transfer.bump,
+        has_one = owner,
+        has_one = new_owner,
+    )]
+    pub transfer: Account<'info, OwnershipTransfer>,
+    #[account(
+        init_if_needed,
+        payer = new_owner,
+        space = VaultRegistry::INIT_SPACE + 8,
+        seeds = [b"registry", new_owner.key().as_ref()],
+        bump,
+    )]
+    pub registry: Account<'info, VaultRegistry>,
+    #[account(
+        mut,
+        seeds = [b"vault", new_owner.key().as_ref(), registry.vault_count.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub new_vault: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = new_owner,
+        space = VaultState::INIT_SPACE + 8,
+        seeds = [b"state", new_vault.key().as_ref()],
+        bump,
+    )]
+    pub new_vault_state: Box<Account<'info, VaultState>>,
+    /// CHECK: only its owner is read, it must not be an open stake account
+    #[account(
+        seeds = [b"stake", vault.key().as_ref()],
+        bump,
+    )]
+    pub stake_account: UncheckedAccount<'info>,
+    /// CHECK: only its owner is read, it must not be an open pending withdrawal
+    #[account(
+        seeds = [b"pending", vault.key().as_ref()],
+        bump,
+    )]
+    pub pending: UncheckedAccount<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultError::Paused,
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct CancelTransfer<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        close = signer,
+        seeds = [b"transfer", vault.key().as_ref()],
+        bump = transfer.bump,
+    )]
+    pub transfer: Account<'info, OwnershipTransfer>,
+}
+
+impl<'info> AcceptTransfer<'info> {
+    /// # Copy the VaultState under the new owner and move the whole balance
+    /// Returns the lamports moved
+    pub fn migrate(&mut self, bumps: &AcceptTransferBumps, token_accounts: &'info [AccountInfo<'info>]) -> Result<u64> {
+        // Streams pay out of the old vault address, settle them before handing it over
+        require_eq!(self.vault_state.reserved, 0, VaultError::FundsReserved);
+        let now = Clock::get()?.unix_timestamp;
+        require!(!self.vault_state.is_frozen(now), VaultError::Frozen);
+        // A transfer moves the whole balance at once, so it is an instant withdrawal too
+        self.vault_state.check_instant_withdrawal(now)?;
+        require!(
+            *self.stake_account.owner != STAKE_PROGRAM_ID && *self.pending.owner != crate::ID,
+            VaultError::VaultInUse
+        );
+
+        let vault_id = self.registry.next_vault_id(self.new_owner.key(), bumps.registry)?;
+
+        // Lock, limits, delay and guardian carry over so a transfer cannot loosen them,
+        // the beneficiary and recovery guardians chosen by the old owner do not
+        let mut state = (**self.vault_state).clone();
+        state.owner = self.new_owner.key();
+        state.vault_id = vault_id;
+        state.beneficiary = None;
+        state.inactivity_period = 0;
+        state.last_heartbeat = now;
+        state.pending_beneficiary = None;
+        state.pending_inactivity_period = 0;
+        state.pending_beneficiary_at = 0;
+        state.recovery_guardians = Vec::new();
+        state.recovery_threshold = 0;
+        state.recovery_delay = 0;
+        state.recovered_to = None;
+        state.next_nonce = 0;
+        state.bump = bumps.new_vault_state;
+        self.new_vault_state.set_inner(state);
+
+        let owner_key = self.owner.key();
+        let old_vault_id = self.transfer.vault_id.to_le_bytes();
+        let signer_seeds: [&[&[u8]]; 1] = [&[b"vault", owner_key.as_ref(), old_vault_id.as_ref(), &[bumps.vault]]];
+        move_vault_tokens(
+            token_accounts,
+            &self.vault.to_account_info(),
+            &signer_seeds,
+            &self.new_vault.key(),
+            &mut self.vault_state,
+            &mut self.new_vault_state,
+        )?;
+
+        let amount = self.vault.lamports();
+        if amount > 0 {
+            transfer_from_vault(
+                &self.system_program,
+                &self.vault,
+                self.new_vault.to_account_info(),
+                self.owner.key(),
+                self.transfer.vault_id,
+                bumps.vault,
+                amount,
+            )?;
+        }
+        Ok(amount)
+    }
+}
+
+pub fn propose_handler(ctx: Context<ProposeTransfer>, vault_id: u64, new_owner: Pubkey) -> Result<()> {
+    require_keys_neq!(new_owner, ctx.accounts.signer.key(), VaultError::InvalidRecipient);
+    ctx.accounts.transfer.set_inner(OwnershipTransfer {
+        owner: ctx.accounts.signer.key(),
+        vault_id,
+        new_owner,
+        bump: ctx.bumps.transfer,
+    });
+    Ok(())
+}
+
+pub fn accept_handler<'info>(ctx: Context<'_, '_, 'info, 'info, AcceptTransfer<'info>>) -> Result<()> {
+    let amount = ctx.accounts.migrate(&ctx.bumps, ctx.remaining_accounts)?;
+
+    let slot = Clock::get()?.slot;
+    emit_cpi!(VaultWithdrawn {
+        owner: ctx.accounts.owner.key(),
+        vault_id: ctx.accounts.transfer.vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot,
+    });
+    emit_cpi!(VaultDeposited {
+        owner: ctx.accounts.new_owner.key(),
+        vault_id: ctx.accounts.new_vault_state.vault_id,
+        amount,
+        new_balance: ctx.accounts.new_vault.lamports(),
+        slot,
+    });
+    Ok(())
+}
+
+pub fn cancel_handler(_ctx: Context<CancelTransfer>, _vault_id: u64) -> Result<()> {
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{transfer, Transfer};
+use crate::state::{Config, VaultState};
+use crate::errors::VaultError;
+use crate::events::VaultWithdrawn;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct Withdraw<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultError::Paused,
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Withdrawing the full balance closes the vault, anything less must keep it rent-exempt
+pub fn check_withdrawal(balance: u64, amount: u64) -> Result<()> {
+    require_gt!(amount, 0, VaultError::InvalidAmount);
+    require_gte!(balance, amount, VaultError::InsufficientFunds);
+    let remaining = balance - amount;
+    if remaining != 0 {
+        require_gte!(remaining, Rent::get()?.minimum_balance(0), VaultError::DustRemaining);
+    }
+    Ok(())
+}
+
+/// # Move lamports out of the vault, signed by the `[b"vault", owner, vault_id, bump]` seeds
+pub fn transfer_from_vault<'info>(
+    system_program: &Program<'info, System>,
+    vault: &SystemAccount<'info>,
+    to: AccountInfo<'info>,
+    owner: Pubkey,
+    vault_id: u64,
+    bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let vault_id = vault_id.to_le_bytes();
+    let signer_seeds = &[b"vault", owner.as_ref(), vault_id.as_ref(), &[bump]];
+    transfer(
+        CpiContext::new_with_signer(
+            system_program.to_account_info(),
+            Transfer {
+                from: vault.to_account_info(),
+                to,
+            },
+            &[&signer_seeds[..]]
+        ),
+        amount
+    )
+}
+
+pub fn handler(ctx: Context<Withdraw>, vault_id: u64, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    ctx.accounts.vault_state.check_instant_withdrawal(clock.unix_timestamp)?;
+    let balance = ctx.accounts.vault.lamports();
+    check_withdrawal(balance, amount)?;
+    ctx.accounts.vault_state.check_outflow(&clock, balance, amount)?;
+
+    transfer_from_vault(
+        &ctx.accounts.system_program,
+        &ctx.accounts.vault,
+        ctx.accounts.signer.to_account_info(),
+        ctx.accounts.signer.key(),
+        vault_id,
+        ctx.bumps.vault,
+        amount,
+    )?;
+    ctx.accounts.vault_state.record_withdrawal(amount)?;
+
+    emit_cpi!(VaultWithdrawn {
+        owner: ctx.accounts.signer.key(),
+        vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
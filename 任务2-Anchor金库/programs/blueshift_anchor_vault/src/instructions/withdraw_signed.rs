@@ -0,0 +1,219 @@
+use anchor_lang::prelude::*;
+use solana_instructions_sysvar::{load_current_index_checked, load_instruction_at_checked};
+use solana_sdk_ids::{ed25519_program, sysvar};
+use crate::state::{Config, VaultState};
+use crate::errors::VaultError;
+use crate::events::VaultWithdrawn;
+use super::withdraw::{check_withdrawal, transfer_from_vault};
+
+// 离线签名取款：所有者对 (vault, recipient, amount, nonce, expiry) 签名，
+// 任何人都可以把签名放进交易里的 Ed25519 预编译指令，再紧跟着调用 withdraw_signed 提交
+
+pub const WITHDRAWAL_MESSAGE_LEN: usize = 32 + 32 + 8 + 8 + 8;
+
+/// The exact bytes the owner signs
+pub fn withdrawal_message(vault: &Pubkey, recipient: &Pubkey, amount: u64, nonce: u64, expiry: i64) -> [u8; WITHDRAWAL_MESSAGE_LEN] {
+    let mut message = [0u8; WITHDRAWAL_MESSAGE_LEN];
+    message[..32].copy_from_slice(vault.as_ref());
+    message[32..64].copy_from_slice(recipient.as_ref());
+    message[64..72].copy_from_slice(&amount.to_le_bytes());
+    message[72..80].copy_from_slice(&nonce.to_le_bytes());
+    message[80..].copy_from_slice(&expiry.to_le_bytes());
+    message
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct WithdrawSigned<'info> {
+    /// Relayer paying the fees, needs no authority over the vault
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub owner: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", owner.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+        has_one = owner,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultError::Paused,
+    )]
+    pub config: Account<'info, Config>,
+    /// CHECK: instructions sysvar
+    #[account(address = sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawSigned<'info> {
+    /// # Check the Ed25519 instruction right before this one signs `message` with the owner key
+    pub fn verify_signature(&self, message: &[u8]) -> Result<()> {
+        let instructions = self.instructions.to_account_info();
+        let current = load_current_index_checked(&instructions)?;
+        require_gt!(current, 0, VaultError::InvalidSignature);
+        let ix = load_instruction_at_checked(current as usize - 1, &instructions)?;
+        require_keys_eq!(ix.program_id, ed25519_program::ID, VaultError::InvalidSignature);
+
+        let (pubkey, signed) = parse_ed25519_instruction(&ix.data)?;
+        require!(pubkey == self.owner.key().as_ref(), VaultError::InvalidSignature);
+        require!(signed == message, VaultError::InvalidSignature);
+        Ok(())
+    }
+}
+
+/// # Pubkey and message of an Ed25519 program instruction carrying a single signature
+fn parse_ed25519_instruction(data: &[u8]) -> Result<(&[u8], &[u8])> {
+    // [num_signatures: u8, padding: u8, offsets: 7 x u16], then signature, pubkey and message
+    require!(data.len() >= 16 && data[0] == 1, VaultError::InvalidSignature);
+    let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]);
+    let signature_ix = read_u16(4);
+    let pubkey_offset = read_u16(6) as usize;
+    let pubkey_ix = read_u16(8);
+    let message_offset = read_u16(10) as usize;
+    let message_size = read_u16(12) as usize;
+    let message_ix = read_u16(14);
+    // Everything must live in the Ed25519 instruction itself, not be pulled from elsewhere
+    require!(
+        signature_ix == u16::MAX && pubkey_ix == u16::MAX && message_ix == u16::MAX,
+        VaultError::InvalidSignature
+    );
+
+    let pubkey = data
+        .get(pubkey_offset..pubkey_offset + 32)
+        .ok_or(VaultError::InvalidSignature)?;
+    let message = data
+        .get(message_offset..message_offset + message_size)
+        .ok_or(VaultError::InvalidSignature)?;
+    Ok((pubkey, message))
+}
+
+pub fn handler(ctx: Context<WithdrawSigned>, vault_id: u64, amount: u64, nonce: u64, expiry: i64) -> Result<()> {
+    let clock = Clock::get()?;
+    require_gte!(expiry, clock.unix_timestamp, VaultError::Expired);
+    require_eq!(nonce, ctx.accounts.vault_state.next_nonce, VaultError::InvalidNonce);
+
+    let message = withdrawal_message(
+        &ctx.accounts.vault.key(),
+        &ctx.accounts.recipient.key(),
+        amount,
+        nonce,
+        expiry,
+    );
+    ctx.accounts.verify_signature(&message)?;
+
+    // Same rules as an owner-signed withdraw_to
+    let vault_state = &mut ctx.accounts.vault_state;
+    vault_state.check_instant_withdrawal(clock.unix_timestamp)?;
+    require!(
+        vault_state.allows_recipient(&ctx.accounts.recipient.key(), clock.unix_timestamp),
+        VaultError::RecipientNotAllowed
+    );
+    let balance = ctx.accounts.vault.lamports();
+    check_withdrawal(balance, amount)?;
+    vault_state.check_outflow(&clock, balance, amount)?;
+    // Consume the nonce so the same message cannot be replayed
+    vault_state.next_nonce += 1;
+
+    transfer_from_vault(
+        &ctx.accounts.system_program,
+        &ctx.accounts.vault,
+        ctx.accounts.recipient.to_account_info(),
+        ctx.accounts.owner.key(),
+        vault_id,
+        ctx.bumps.vault,
+        amount,
+    )?;
+    ctx.accounts.vault_state.record_withdrawal(amount)?;
+
+    emit_cpi!(VaultWithdrawn {
+        owner: ctx.accounts.owner.key(),
+        vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot: clock.slot,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Layout the Ed25519 program expects for one signature, as built by the client SDKs
+    fn ed25519_data(pubkey: &[u8; 32], message: &[u8]) -> Vec<u8> {
+        let signature_offset: u16 = 16;
+        let pubkey_offset = signature_offset + 64;
+        let message_offset = pubkey_offset + 32;
+        let mut data = vec![1, 0];
+        for field in [
+            signature_offset,
+            u16::MAX,
+            pubkey_offset,
+            u16::MAX,
+            message_offset,
+            message.len() as u16,
+            u16::MAX,
+        ] {
+            data.extend_from_slice(&field.to_le_bytes());
+        }
+        data.extend_from_slice(&[0; 64]);
+        data.extend_from_slice(pubkey);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn parses_pubkey_and_message() {
+        let owner = Pubkey::new_unique();
+        let message = withdrawal_message(&Pubkey::new_unique(), &Pubkey::new_unique(), 10, 0, 100);
+        let data = ed25519_data(&owner.to_bytes(), &message);
+        let (pubkey, signed) = parse_ed25519_instruction(&data).unwrap();
+        assert_eq!(pubkey, owner.as_ref());
+        assert_eq!(signed, message.as_slice());
+    }
+
+    #[test]
+    fn rejects_offsets_into_other_instructions() {
+        let mut data = ed25519_data(&[7; 32], b"message");
+        // pubkey_instruction_index pointing at instruction 0
+        data[8..10].copy_from_slice(&0u16.to_le_bytes());
+        assert!(parse_ed25519_instruction(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_several_signatures() {
+        let mut data = ed25519_data(&[7; 32], b"message");
+        data[0] = 2;
+        assert!(parse_ed25519_instruction(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_bounds_and_short_data() {
+        let mut data = ed25519_data(&[7; 32], b"message");
+        data[12..14].copy_from_slice(&u16::MAX.to_le_bytes());
+        assert!(parse_ed25519_instruction(&data).is_err());
+        assert!(parse_ed25519_instruction(&[1; 15]).is_err());
+    }
+
+    #[test]
+    fn message_binds_every_field() {
+        let vault = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let message = withdrawal_message(&vault, &recipient, 1, 2, 3);
+        assert_ne!(message, withdrawal_message(&recipient, &vault, 1, 2, 3));
+        assert_ne!(message, withdrawal_message(&vault, &recipient, 1, 3, 3));
+        assert_ne!(message, withdrawal_message(&vault, &recipient, 1, 2, 4));
+    }
+}
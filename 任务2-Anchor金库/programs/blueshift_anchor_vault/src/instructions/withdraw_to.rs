@@ -0,0 +1,66 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, VaultState};
+use crate::errors::VaultError;
+use crate::events::VaultWithdrawn;
+use super::withdraw::{check_withdrawal, transfer_from_vault};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct WithdrawTo<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultError::Paused,
+    )]
+    pub config: Account<'info, Config>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn handler(ctx: Context<WithdrawTo>, vault_id: u64, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    ctx.accounts.vault_state.check_instant_withdrawal(clock.unix_timestamp)?;
+    let balance = ctx.accounts.vault.lamports();
+    check_withdrawal(balance, amount)?;
+    ctx.accounts.vault_state.check_outflow(&clock, balance, amount)?;
+    require!(
+        ctx.accounts.vault_state.allows_recipient(&ctx.accounts.recipient.key(), clock.unix_timestamp),
+        VaultError::RecipientNotAllowed
+    );
+
+    transfer_from_vault(
+        &ctx.accounts.system_program,
+        &ctx.accounts.vault,
+        ctx.accounts.recipient.to_account_info(),
+        ctx.accounts.signer.key(),
+        vault_id,
+        ctx.bumps.vault,
+        amount,
+    )?;
+    ctx.accounts.vault_state.record_withdrawal(amount)?;
+
+    emit_cpi!(VaultWithdrawn {
+        owner: ctx.accounts.signer.key(),
+        vault_id,
+        amount,
+        new_balance: ctx.accounts.vault.lamports(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    close_account, transfer_checked, CloseAccount, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+use anchor_spl::associated_token::{get_associated_token_address_with_program_id, AssociatedToken};
+use crate::state::{Config, VaultState};
+use crate::errors::VaultError;
+
+#[derive(Accounts)]
+#[instruction(vault_id: u64)]
+pub struct WithdrawToken<'info> {
+    #[account(mut)]
+    pub signer: Signer<'info>,
+    #[account(
+        seeds = [b"vault", signer.key().as_ref(), vault_id.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"state", vault.key().as_ref()],
+        bump = vault_state.bump,
+    )]
+    pub vault_state: Account<'info, VaultState>,
+    /// Token Accounts
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program
+    )]
+    pub vault_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = signer,
+        associated_token::mint = mint,
+        associated_token::authority = signer,
+        associated_token::token_program = token_program
+    )]
+    pub signer_ata: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.paused @ VaultError::Paused,
+    )]
+    pub config: Account<'info, Config>,
+    /// Programs
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawToken<'info> {
+    /// # Withdraw the tokens, signed by the vault PDA
+    pub fn withdraw_tokens(&self, vault_id: u64, amount: u64, bump: u8) -> Result<()> {
+        let signer_key = self.signer.key();
+        let vault_id = vault_id.to_le_bytes();
+        let signer_seeds: [&[&[u8]]; 1] = [&[b"vault", signer_key.as_ref(), vault_id.as_ref(), &[bump]]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault_ata.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.signer_ata.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            amount,
+            self.mint.decimals,
+        )?;
+
+        // An emptied ATA is closed so `token_mints` only lists mints that still hold a balance
+        if amount == self.vault_ata.amount {
+            close_account(CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                CloseAccount {
+                    account: self.vault_ata.to_account_info(),
+                    destination: self.signer.to_account_info(),
+                    authority: self.vault.to_account_info(),
+                },
+                &signer_seeds,
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+/// # Move every tracked token balance of a vault to another vault
+/// `accounts` holds one `[token_program, mint, from_ata, to_ata]` group per mint in
+/// `from.token_mints`, the target ATAs must already exist. The emptied source ATAs are closed
+/// into the vault, so their rent moves along with its lamports afterwards
+pub fn move_vault_tokens<'info>(
+    accounts: &'info [AccountInfo<'info>],
+    vault: &AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    to_vault: &Pubkey,
+    from: &mut VaultState,
+    to: &mut VaultState,
+) -> Result<()> {
+    require!(accounts.len() % 4 == 0, VaultError::InvalidTokenAccount);
+    for group in accounts.chunks_exact(4) {
+        let [token_program, mint, from_ata, to_ata] = group else {
+            return err!(VaultError::InvalidTokenAccount);
+        };
+        require!(
+            token_program.key() == anchor_spl::token::ID || token_program.key() == anchor_spl::token_2022::ID,
+            VaultError::InvalidTokenAccount
+        );
+        require_keys_eq!(*mint.owner, token_program.key(), VaultError::InvalidTokenAccount);
+        require_keys_eq!(
+            from_ata.key(),
+            get_associated_token_address_with_program_id(&vault.key(), &mint.key(), &token_program.key()),
+            VaultError::InvalidTokenAccount
+        );
+        require_keys_eq!(
+            to_ata.key(),
+            get_associated_token_address_with_program_id(to_vault, &mint.key(), &token_program.key()),
+            VaultError::InvalidTokenAccount
+        );
+
+        let decimals = InterfaceAccount::<Mint>::try_from(mint)?.decimals;
+        let amount = InterfaceAccount::<TokenAccount>::try_from(from_ata)?.amount;
+        transfer_checked(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                TransferChecked {
+                    from: from_ata.clone(),
+                    mint: mint.clone(),
+                    to: to_ata.clone(),
+                    authority: vault.clone(),
+                },
+                signer_seeds,
+            ),
+            amount,
+            decimals,
+        )?;
+        close_account(CpiContext::new_with_signer(
+            token_program.clone(),
+            CloseAccount {
+                account: from_ata.clone(),
+                destination: vault.clone(),
+                authority: vault.clone(),
+            },
+            signer_seeds,
+        ))?;
+
+        from.token_mints.retain(|tracked| tracked != mint.key);
+        to.track_mint(mint.key())?;
+    }
+    require!(from.token_mints.is_empty(), VaultError::TokensRemaining);
+    Ok(())
+}
+
+pub fn handler(ctx: Context<WithdrawToken>, vault_id: u64, amount: u64) -> Result<()> {
+    require_gt!(amount, 0, VaultError::InvalidAmount);
+    ctx.accounts.vault_state.check_token_outflow(Clock::get()?.unix_timestamp)?;
+    require_gte!(ctx.accounts.vault_ata.amount, amount, VaultError::InsufficientFunds);
+    ctx.accounts.withdraw_tokens(vault_id, amount, ctx.bumps.vault)?;
+    if amount == ctx.accounts.vault_ata.amount {
+        let mint = ctx.accounts.mint.key();
+        ctx.accounts.vault_state.token_mints.retain(|tracked| *tracked != mint);
+    }
+    Ok(())
+}
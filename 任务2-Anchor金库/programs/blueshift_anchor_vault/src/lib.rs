@@ -1,5 +1,12 @@
 use anchor_lang::prelude::*;
-use anchor_lang::system_program::{transfer, Transfer};
+
+mod state;
+mod errors;
+mod instructions;
+pub mod events;
+
+use instructions::*;
+pub use instructions::withdraw_signed::{withdrawal_message, WITHDRAWAL_MESSAGE_LEN};
 
 declare_id!("22222222222222222222222222222222222222222222");
 
@@ -7,68 +14,302 @@ declare_id!("22222222222222222222222222222222222222222222");
 pub mod blueshift_anchor_vault {
     use super::*;
 
-    pub fn deposit(ctx: Context<VaultAction>, amount: u64) -> Result<()> {
-        // Check if vault is empty
-        require_eq!(ctx.accounts.vault.lamports(), 0, VaultError::VaultAlreadyExists);
-
-        // Ensure amount exceeds rent-exempt minimum
-        require_gt!(amount, Rent::get()?.minimum_balance(0), VaultError::InvalidAmount);
-
-        transfer(
-            CpiContext::new(
-                ctx.accounts.system_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.signer.to_account_info(),
-                    to: ctx.accounts.vault.to_account_info(),
-                },
-            ),
-            amount,
-        )?;
-
-        Ok(())
-    }
-
-    pub fn withdraw(ctx: Context<VaultAction>) -> Result<()> {
-        // Check if vault has any lamports
-        require_neq!(ctx.accounts.vault.lamports(), 0, VaultError::InvalidAmount);
-
-        // Create PDA signer seeds
-        let signer_key = ctx.accounts.signer.key();
-        let signer_seeds = &[b"vault", signer_key.as_ref(), &[ctx.bumps.vault]];
-        // Transfer all lamports from vault to signer
-        transfer(
-            CpiContext::new_with_signer(
-                ctx.accounts.system_program.to_account_info(),
-                Transfer {
-                    from: ctx.accounts.vault.to_account_info(),
-                    to: ctx.accounts.signer.to_account_info(),
-                },
-                &[&signer_seeds[..]]
-            ),
-            ctx.accounts.vault.lamports()
-        )?;
-        Ok(())
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        deposit_fee_bps: u16, // 存款手续费（万分之几），上限 MAX_DEPOSIT_FEE_BPS
+        treasury: Pubkey      // 手续费收款地址
+    ) -> Result<()> {
+        instructions::config::initialize_handler(ctx, deposit_fee_bps, treasury)
     }
-}
 
-// 前端传递数据的时候，是根据IDL传递的，IDL已经约束了每个账号，mut，singer这些都不需要显式传递，但是实际上已经传了
-#[derive(Accounts)]
-pub struct VaultAction<'info> {
-    #[account(mut)]
-    pub signer: Signer<'info>,
-    #[account(
-        mut,
-        seeds = [b"vault", signer.key().as_ref()],
-        bump,
-    )]
-    pub vault: SystemAccount<'info>,
-    pub system_program: Program<'info, System>,
-}
+    pub fn set_paused(ctx: Context<UpdateConfig>, paused: bool) -> Result<()> {
+        instructions::config::set_paused_handler(ctx, paused)
+    }
+
+    pub fn set_fee(ctx: Context<UpdateConfig>, deposit_fee_bps: u16, treasury: Pubkey) -> Result<()> {
+        instructions::config::set_fee_handler(ctx, deposit_fee_bps, treasury)
+    }
+
+    pub fn propose_admin(ctx: Context<UpdateConfig>, new_admin: Option<Pubkey>) -> Result<()> {
+        // Two-step handover: the new admin has to accept before it takes effect
+        instructions::config::propose_admin_handler(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::config::accept_admin_handler(ctx)
+    }
+
+    pub fn open_vault(
+        ctx: Context<OpenVault>,
+        label: String // 子金库名称，例如 "rent"、"savings"
+    ) -> Result<()> {
+        // The new vault takes the next id from the owner's registry
+        instructions::open_vault::handler(ctx, label)
+    }
+
+    pub fn list_vaults(ctx: Context<ListVaults>) -> Result<Vec<Pubkey>> {
+        instructions::open_vault::list_handler(ctx)
+    }
+
+    pub fn migrate_legacy_vault<'info>(
+        ctx: Context<'_, '_, 'info, 'info, MigrateLegacyVault<'info>>,
+        vault_id: u64 // 目标子金库编号：必须先 open_vault；旧金库 [b"vault", owner] 的代币和余额都搬进来
+    ) -> Result<()> {
+        instructions::legacy_vault::handler(ctx, vault_id)
+    }
+
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        vault_id: u64,         // 子金库编号：由 open_vault 分配
+        amount: u64,
+        unlock_at: Option<i64> // 可选的解锁时间：在此之前无法取款，只能延长不能提前
+    ) -> Result<()> {
+        instructions::deposit::handler(ctx, vault_id, amount, unlock_at)
+    }
+
+    pub fn withdraw(ctx: Context<Withdraw>, vault_id: u64, amount: u64) -> Result<()> {
+        // Withdrawing the full balance closes the vault
+        instructions::withdraw::handler(ctx, vault_id, amount)
+    }
+
+    pub fn deposit_token(ctx: Context<DepositToken>, vault_id: u64, amount: u64) -> Result<()> {
+        // Tokens sit in an ATA owned by the same vault PDA that holds the SOL
+        instructions::deposit_token::handler(ctx, vault_id, amount)
+    }
+
+    pub fn withdraw_token(ctx: Context<WithdrawToken>, vault_id: u64, amount: u64) -> Result<()> {
+        // 代币没有延迟取款流程：设置了取款延迟、锁定期或每 epoch 限额时直接拒绝
+        instructions::withdraw_token::handler(ctx, vault_id, amount)
+    }
 
-#[error_code]
-pub enum VaultError {
-    #[msg("Vault already exists")]
-    VaultAlreadyExists,
-    #[msg("Invalid amount")]
-    InvalidAmount,
-}
\ No newline at end of file
+    pub fn withdraw_to(ctx: Context<WithdrawTo>, vault_id: u64, amount: u64) -> Result<()> {
+        // Pay a third party straight out of the vault, still authorized by the owner
+        instructions::withdraw_to::handler(ctx, vault_id, amount)
+    }
+
+    pub fn withdraw_signed(
+        ctx: Context<WithdrawSigned>,
+        vault_id: u64,
+        amount: u64,
+        nonce: u64, // 必须等于 vault_state.next_nonce，用过即作废，防止重放
+        expiry: i64 // 过期时间：超过后签名失效
+    ) -> Result<()> {
+        // The owner's signature is checked through the preceding Ed25519 instruction
+        instructions::withdraw_signed::handler(ctx, vault_id, amount, nonce, expiry)
+    }
+
+    pub fn set_recipients(ctx: Context<VaultSettings>, vault_id: u64, recipients: Vec<Pubkey>) -> Result<()> {
+        // 空列表表示不限制收款人；新增收款人或清空列表需要等待 LIMIT_CHANGE_DELAY，删减立即生效
+        instructions::settings::set_recipients_handler(ctx, vault_id, recipients)
+    }
+
+    pub fn set_withdraw_limit(
+        ctx: Context<VaultSettings>,
+        vault_id: u64,
+        epoch_limit: u64 // 每个 epoch 最多可取出的 lamports，0 表示不限制；放宽限制需要等待 LIMIT_CHANGE_DELAY
+    ) -> Result<()> {
+        instructions::settings::set_withdraw_limit_handler(ctx, vault_id, epoch_limit)
+    }
+
+    pub fn set_withdraw_delay(
+        ctx: Context<VaultSettings>,
+        vault_id: u64,
+        withdraw_delay: i64,     // 取款延迟（秒）：大于 0 时只能走 request/execute 流程；缩短需要等待当前延迟
+        guardian: Option<Pubkey> // 守护者：可以在延迟期内取消待执行的取款；移除或更换守护者需要等待当前延迟
+    ) -> Result<()> {
+        instructions::settings::set_withdraw_delay_handler(ctx, vault_id, withdraw_delay, guardian)
+    }
+
+    pub fn set_freeze_cooldown(
+        ctx: Context<VaultSettings>,
+        vault_id: u64,
+        freeze_cooldown: i64 // 解冻冷却期（秒）：unfreeze 之后还要等这么久才能取款，不短于 MIN_FREEZE_COOLDOWN；冻结期间不能修改
+    ) -> Result<()> {
+        instructions::settings::set_freeze_cooldown_handler(ctx, vault_id, freeze_cooldown)
+    }
+
+    pub fn freeze(ctx: Context<VaultSettings>, vault_id: u64) -> Result<()> {
+        // Panic button: blocks every outflow until unfreeze plus the cooldown
+        instructions::settings::freeze_handler(ctx, vault_id)
+    }
+
+    pub fn unfreeze(ctx: Context<VaultSettings>, vault_id: u64) -> Result<()> {
+        instructions::settings::unfreeze_handler(ctx, vault_id)
+    }
+
+    pub fn request_withdraw(ctx: Context<RequestWithdraw>, vault_id: u64, amount: u64) -> Result<()> {
+        instructions::delayed_withdraw::request_handler(ctx, vault_id, amount)
+    }
+
+    pub fn execute_withdraw(ctx: Context<ExecuteWithdraw>, vault_id: u64) -> Result<()> {
+        instructions::delayed_withdraw::execute_handler(ctx, vault_id)
+    }
+
+    pub fn cancel_withdraw(ctx: Context<CancelWithdraw>, vault_id: u64) -> Result<()> {
+        // Owner or guardian can cancel during the delay window
+        instructions::delayed_withdraw::cancel_handler(ctx, vault_id)
+    }
+
+    pub fn set_beneficiary(
+        ctx: Context<VaultSettings>,
+        vault_id: u64,
+        beneficiary: Option<Pubkey>, // 受益人：None 表示取消并立即生效；设置新受益人需要等待 max(LIMIT_CHANGE_DELAY, 取款延迟)
+        inactivity_period: i64       // 不活跃期（秒）：至少 MIN_INACTIVITY_PERIOD，超过这段时间没有 heartbeat，受益人即可 claim
+    ) -> Result<()> {
+        instructions::settings::set_beneficiary_handler(ctx, vault_id, beneficiary, inactivity_period)
+    }
+
+    pub fn heartbeat(ctx: Context<VaultSettings>, vault_id: u64) -> Result<()> {
+        // Owner proves liveness and resets the dead-man switch
+        instructions::settings::heartbeat_handler(ctx, vault_id)
+    }
+
+    pub fn claim(ctx: Context<Claim>, vault_id: u64) -> Result<()> {
+        instructions::claim::handler(ctx, vault_id)
+    }
+
+    pub fn set_recovery_guardians(
+        ctx: Context<VaultSettings>,
+        vault_id: u64,
+        guardians: Vec<Pubkey>, // 恢复守护者：最多 MAX_RECOVERY_GUARDIANS 个，空列表表示关闭恢复
+        threshold: u8,          // 门限：执行恢复所需的最少守护者批准数
+        recovery_delay: i64     // 恢复等待期（秒）：提案创建后至少等这么久才能执行，不短于 MIN_RECOVERY_DELAY
+    ) -> Result<()> {
+        instructions::settings::set_recovery_guardians_handler(ctx, vault_id, guardians, threshold, recovery_delay)
+    }
+
+    pub fn propose_recovery(
+        ctx: Context<ProposeRecovery>,
+        vault_id: u64,
+        new_owner: Pubkey,  // 新所有者
+        new_vault_id: u64   // 新所有者已经 open_vault 的子金库编号
+    ) -> Result<()> {
+        instructions::recovery::propose_handler(ctx, vault_id, new_owner, new_vault_id)
+    }
+
+    pub fn approve_recovery(ctx: Context<ApproveRecovery>) -> Result<()> {
+        instructions::recovery::approve_handler(ctx)
+    }
+
+    pub fn execute_recovery<'info>(ctx: Context<'_, '_, 'info, 'info, ExecuteRecovery<'info>>) -> Result<()> {
+        // remaining_accounts：每个代币一组 [token_program, mint, 旧金库 ATA, 新金库 ATA]
+        instructions::recovery::execute_handler(ctx)
+    }
+
+    pub fn cancel_recovery(ctx: Context<CancelRecovery>) -> Result<()> {
+        // An owner who still has the key can veto a recovery, a proposer can withdraw their own
+        instructions::recovery::cancel_handler(ctx)
+    }
+
+    pub fn propose_transfer(
+        ctx: Context<ProposeTransfer>,
+        vault_id: u64,
+        new_owner: Pubkey // 新所有者：必须自己调用 accept_transfer 才会生效
+    ) -> Result<()> {
+        instructions::transfer_ownership::propose_handler(ctx, vault_id, new_owner)
+    }
+
+    pub fn accept_transfer<'info>(ctx: Context<'_, '_, 'info, 'info, AcceptTransfer<'info>>) -> Result<()> {
+        // Opens the next sub-vault of the new owner and moves the balance, tokens and VaultState there
+        // remaining_accounts 的格式同 execute_recovery
+        instructions::transfer_ownership::accept_handler(ctx)
+    }
+
+    pub fn cancel_transfer(ctx: Context<CancelTransfer>, vault_id: u64) -> Result<()> {
+        instructions::transfer_ownership::cancel_handler(ctx, vault_id)
+    }
+
+    pub fn create_stream(
+        ctx: Context<CreateStream>,
+        vault_id: u64,
+        recipient: Pubkey, // 收款人：每个金库对同一收款人只能有一个 stream，必须在收款人白名单内
+        start: i64,        // 开始时间：从此刻起线性释放，不能早于当前时间，且 end - start 不能短于取款延迟
+        cliff: i64,        // 悬崖期：在此之前一分钱都取不出
+        end: i64,          // 结束时间：此后全部释放
+        total: u64         // 总额：创建时从金库中预留，所有者不能再取走
+    ) -> Result<()> {
+        instructions::stream::create_handler(ctx, vault_id, recipient, start, cliff, end, total)
+    }
+
+    pub fn withdraw_vested(ctx: Context<WithdrawVested>, amount: u64) -> Result<()> {
+        // Recipient pulls any amount up to what has vested so far, counted against the epoch limit
+        instructions::stream::withdraw_handler(ctx, amount)
+    }
+
+    pub fn create_stake(
+        ctx: Context<CreateStake>,
+        vault_id: u64,
+        amount: u64 // 质押数量：包含质押账户的免租金额，委托给 vote_account 对应的验证者
+    ) -> Result<()> {
+        instructions::stake::create_handler(ctx, vault_id, amount)
+    }
+
+    pub fn deactivate_stake(ctx: Context<DeactivateStake>, vault_id: u64) -> Result<()> {
+        // Owner only, until the vault is recovered and anyone may unwind the stake
+        instructions::stake::deactivate_handler(ctx, vault_id)
+    }
+
+    pub fn withdraw_stake(ctx: Context<WithdrawStake>, vault_id: u64) -> Result<()> {
+        // Once cooled down, anyone can send principal and rewards back into the vault,
+        // or into the new vault after a recovery
+        instructions::stake::withdraw_handler(ctx, vault_id)
+    }
+
+    pub fn authorize_subscription(
+        ctx: Context<AuthorizeSubscription>,
+        vault_id: u64,
+        merchant: Pubkey, // 商户：每个周期可以从金库拉取一次
+        amount: u64,      // 每期金额
+        period: i64       // 周期（秒）
+    ) -> Result<()> {
+        instructions::subscription::authorize_handler(ctx, vault_id, merchant, amount, period)
+    }
+
+    pub fn charge(ctx: Context<Charge>) -> Result<()> {
+        // Permissionless: pays the merchant once the current period is due
+        instructions::subscription::charge_handler(ctx)
+    }
+
+    pub fn cancel_subscription(ctx: Context<CancelSubscription>, vault_id: u64, merchant: Pubkey) -> Result<()> {
+        instructions::subscription::cancel_handler(ctx, vault_id, merchant)
+    }
+
+    pub fn create_multisig(
+        ctx: Context<CreateMultisig>,
+        seed: u64,            // 唯一种子：同一创建者可以创建多个多签金库
+        members: Vec<Pubkey>, // 成员列表：最多 MAX_MULTISIG_MEMBERS 个
+        threshold: u8         // 门限 M：执行取款所需的最少批准数
+    ) -> Result<()> {
+        instructions::multisig::create_handler(ctx, seed, members, threshold)
+    }
+
+    pub fn multisig_propose(ctx: Context<Propose>, recipient: Pubkey, amount: u64) -> Result<()> {
+        instructions::multisig::propose_handler(ctx, recipient, amount)
+    }
+
+    pub fn multisig_approve(ctx: Context<Approve>) -> Result<()> {
+        instructions::multisig::approve_handler(ctx)
+    }
+
+    pub fn multisig_execute(ctx: Context<Execute>) -> Result<()> {
+        instructions::multisig::execute_handler(ctx)
+    }
+
+    pub fn create_pool(
+        ctx: Context<CreatePool>,
+        seed: u64 // 唯一种子：同一创建者可以创建多个共享金库
+    ) -> Result<()> {
+        instructions::pool::create_handler(ctx, seed)
+    }
+
+    pub fn pool_deposit(ctx: Context<PoolDeposit>, amount: u64) -> Result<()> {
+        instructions::pool::deposit_handler(ctx, amount)
+    }
+
+    pub fn pool_withdraw(
+        ctx: Context<PoolWithdraw>,
+        shares: u64 // 赎回的份额，按当前汇率换成 lamports（向下取整）
+    ) -> Result<()> {
+        instructions::pool::withdraw_handler(ctx, shares)
+    }
+}
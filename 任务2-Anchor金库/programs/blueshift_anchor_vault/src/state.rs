@@ -0,0 +1,713 @@
+use anchor_lang::prelude::*;
+use crate::errors::VaultError;
+
+pub const MAX_RECIPIENTS: usize = 8;
+pub const MAX_LABEL_LEN: usize = 32;
+pub const MAX_VAULTS_PER_OWNER: u64 = 16;
+pub const MAX_MULTISIG_MEMBERS: usize = 10;
+pub const MAX_RECOVERY_GUARDIANS: usize = 8;
+pub const MAX_TOKEN_MINTS: usize = 8;
+pub const MAX_DEPOSIT_FEE_BPS: u16 = 1_000;
+/// Virtual shares and lamports added to both sides of the pool exchange rate,
+/// so a first depositor cannot inflate the share price with a donation
+pub const POOL_VIRTUAL_SHARES: u128 = 1_000;
+pub const POOL_VIRTUAL_LAMPORTS: u128 = 1;
+/// Delay before a looser withdrawal limit or allowlist takes effect
+pub const LIMIT_CHANGE_DELAY: i64 = 24 * 60 * 60;
+/// Shortest `inactivity_period`, so the owner always has time to notice and send a heartbeat
+pub const MIN_INACTIVITY_PERIOD: i64 = 7 * 24 * 60 * 60;
+/// Shortest `freeze_cooldown`, so `unfreeze` and a withdrawal cannot land in one transaction
+pub const MIN_FREEZE_COOLDOWN: i64 = 24 * 60 * 60;
+/// Shortest `recovery_delay`, so the owner can cancel a hostile recovery in time
+pub const MIN_RECOVERY_DELAY: i64 = 3 * 24 * 60 * 60;
+
+/// Program-wide settings, a single `[b"config"]` PDA
+#[derive(InitSpace)]
+#[account]
+pub struct Config {
+    pub admin: Pubkey,
+    /// Set by `propose_admin`, becomes `admin` once it calls `accept_admin`
+    pub pending_admin: Option<Pubkey>,
+    pub paused: bool,
+    pub deposit_fee_bps: u16,
+    pub treasury: Pubkey,
+    pub bump: u8,
+}
+
+impl Config {
+    /// Fee skimmed from a deposit, rounded down
+    pub fn deposit_fee(&self, amount: u64) -> u64 {
+        (amount as u128 * self.deposit_fee_bps as u128 / 10_000) as u64
+    }
+}
+
+/// Program-owned ledger kept next to the `vault` SystemAccount
+#[derive(InitSpace, Default)]
+#[account]
+pub struct VaultState {
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    #[max_len(MAX_LABEL_LEN)]
+    pub label: String,
+    pub total_deposited: u64,
+    pub total_withdrawn: u64,
+    /// Unix timestamp before which `withdraw` is refused, 0 when unlocked
+    pub unlock_at: i64,
+    /// Allowed `withdraw_to` recipients, empty means any recipient
+    #[max_len(MAX_RECIPIENTS)]
+    pub recipients: Vec<Pubkey>,
+    /// Looser allowlist waiting for `pending_recipients_at`
+    #[max_len(MAX_RECIPIENTS)]
+    pub pending_recipients: Vec<Pubkey>,
+    /// When `pending_recipients` takes effect, 0 when nothing is pending
+    pub pending_recipients_at: i64,
+    /// Max lamports withdrawn per epoch, 0 means unlimited
+    pub epoch_limit: u64,
+    /// Looser limit waiting for `pending_limit_at`
+    pub pending_epoch_limit: u64,
+    /// When `pending_epoch_limit` takes effect, 0 when nothing is pending
+    pub pending_limit_at: i64,
+    pub withdraw_epoch: u64,
+    pub epoch_withdrawn: u64,
+    /// May `claim` the lamports once the owner has been inactive for `inactivity_period`
+    pub beneficiary: Option<Pubkey>,
+    pub inactivity_period: i64,
+    pub last_heartbeat: i64,
+    /// Beneficiary and inactivity period waiting for `pending_beneficiary_at`
+    pub pending_beneficiary: Option<Pubkey>,
+    pub pending_inactivity_period: i64,
+    /// When `pending_beneficiary` takes effect, 0 when nothing is pending
+    pub pending_beneficiary_at: i64,
+    /// Lamports owed to vesting streams that the owner can no longer withdraw
+    pub reserved: u64,
+    /// Seconds between `request_withdraw` and `execute_withdraw`, 0 allows instant withdrawals
+    pub withdraw_delay: i64,
+    /// Shorter delay waiting for `pending_delay_at`
+    pub pending_withdraw_delay: i64,
+    pub pending_delay_at: i64,
+    /// May cancel pending withdrawals alongside the owner
+    pub guardian: Option<Pubkey>,
+    /// Replacement guardian waiting for `pending_guardian_at`, None removes the guardian
+    pub pending_guardian: Option<Pubkey>,
+    /// When `pending_guardian` takes effect, 0 when nothing is pending
+    pub pending_guardian_at: i64,
+    /// Guardians that can jointly move the vault to a new owner if the owner key is lost
+    #[max_len(MAX_RECOVERY_GUARDIANS)]
+    pub recovery_guardians: Vec<Pubkey>,
+    pub recovery_threshold: u8,
+    /// Seconds between `propose_recovery` and `execute_recovery`
+    pub recovery_delay: i64,
+    /// Vault the balance was moved to by `execute_recovery`, where late stake withdrawals go too
+    pub recovered_to: Option<Pubkey>,
+    /// Nonce the next owner-signed `withdraw_signed` message must carry
+    pub next_nonce: u64,
+    /// Set by `freeze`, blocks every outflow until `unfreeze_at` has passed
+    pub frozen: bool,
+    /// Seconds between `unfreeze` and the vault actually thawing
+    pub freeze_cooldown: i64,
+    /// When a requested unfreeze takes effect, 0 while no unfreeze is requested
+    pub unfreeze_at: i64,
+    /// Mints deposited through `deposit_token` whose vault ATA is still open
+    #[max_len(MAX_TOKEN_MINTS)]
+    pub token_mints: Vec<Pubkey>,
+    pub bump: u8,
+}
+
+impl VaultState {
+    pub fn is_locked(&self, now: i64) -> bool {
+        now < self.unlock_at
+    }
+
+    pub fn allows_recipient(&mut self, recipient: &Pubkey, now: i64) -> bool {
+        let recipients = self.current_recipients(now);
+        recipients.is_empty() || recipients.contains(recipient)
+    }
+
+    /// Current allowlist, applying a pending looser list once it is due
+    pub fn current_recipients(&mut self, now: i64) -> &[Pubkey] {
+        if self.pending_recipients_at != 0 && now >= self.pending_recipients_at {
+            self.recipients = std::mem::take(&mut self.pending_recipients);
+            self.pending_recipients_at = 0;
+        }
+        &self.recipients
+    }
+
+    /// # Change the allowlist
+    /// Dropping entries applies at once, adding entries or clearing the list only after `LIMIT_CHANGE_DELAY`,
+    /// so a single signed transaction cannot both allow and pay a new recipient
+    pub fn set_recipients(&mut self, recipients: Vec<Pubkey>, now: i64) {
+        let current = self.current_recipients(now);
+        let tighter = !recipients.is_empty()
+            && (current.is_empty() || recipients.iter().all(|recipient| current.contains(recipient)));
+        if tighter {
+            self.recipients = recipients;
+            self.pending_recipients = Vec::new();
+            self.pending_recipients_at = 0;
+        } else {
+            self.pending_recipients = recipients;
+            self.pending_recipients_at = now + LIMIT_CHANGE_DELAY;
+        }
+    }
+
+    pub fn record_deposit(&mut self, amount: u64) -> Result<()> {
+        self.total_deposited = self
+            .total_deposited
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn is_frozen(&self, now: i64) -> bool {
+        self.frozen && (self.unfreeze_at == 0 || now < self.unfreeze_at)
+    }
+
+    pub fn is_claimable(&self, now: i64) -> bool {
+        self.beneficiary.is_some() && now >= self.last_heartbeat.saturating_add(self.inactivity_period)
+    }
+
+    /// Current beneficiary, applying a pending one once it is due
+    pub fn current_beneficiary(&mut self, now: i64) -> Option<Pubkey> {
+        if self.pending_beneficiary_at != 0 && now >= self.pending_beneficiary_at {
+            self.beneficiary = self.pending_beneficiary.take();
+            self.inactivity_period = self.pending_inactivity_period;
+            self.pending_inactivity_period = 0;
+            self.pending_beneficiary_at = 0;
+        }
+        self.beneficiary
+    }
+
+    /// # Change the beneficiary
+    /// Removing it applies at once, naming one only after `LIMIT_CHANGE_DELAY` or the withdrawal delay,
+    /// whichever is longer, so a stolen key cannot name itself and claim before the owner reacts
+    pub fn set_beneficiary(&mut self, beneficiary: Option<Pubkey>, inactivity_period: i64, now: i64) {
+        if beneficiary.is_none() {
+            self.beneficiary = None;
+            self.inactivity_period = 0;
+            self.pending_beneficiary = None;
+            self.pending_inactivity_period = 0;
+            self.pending_beneficiary_at = 0;
+        } else {
+            let delay = LIMIT_CHANGE_DELAY.max(self.current_withdraw_delay(now));
+            self.pending_beneficiary = beneficiary;
+            self.pending_inactivity_period = inactivity_period;
+            self.pending_beneficiary_at = now + delay;
+        }
+    }
+
+    /// Lamports the owner can still move, i.e. not owed to a stream
+    pub fn unreserved(&self, balance: u64) -> u64 {
+        balance.saturating_sub(self.reserved)
+    }
+
+    /// # Lamports that can leave the vault without starving its streams
+    /// While a stream is open the vault also keeps its rent-exempt minimum `rent`,
+    /// so every `withdraw_vested` can still leave a valid balance behind
+    pub fn spendable(&self, balance: u64, rent: u64) -> u64 {
+        if self.reserved == 0 {
+            balance
+        } else {
+            balance.saturating_sub(self.reserved.saturating_add(rent))
+        }
+    }
+
+    /// # Check an owner outflow against the freeze, lock, stream reservations and the per-epoch limit
+    pub fn check_outflow(&mut self, clock: &Clock, balance: u64, amount: u64) -> Result<()> {
+        require!(!self.is_frozen(clock.unix_timestamp), VaultError::Frozen);
+        require!(!self.is_locked(clock.unix_timestamp), VaultError::StillLocked);
+        let rent = Rent::get()?.minimum_balance(0);
+        require_gte!(self.spendable(balance, rent), amount, VaultError::FundsReserved);
+        self.record_epoch_outflow(clock, amount)
+    }
+
+    /// # Count an outflow against the per-epoch limit
+    pub fn record_epoch_outflow(&mut self, clock: &Clock, amount: u64) -> Result<()> {
+        let epoch_limit = self.current_epoch_limit(clock.unix_timestamp);
+        if clock.epoch != self.withdraw_epoch {
+            self.withdraw_epoch = clock.epoch;
+            self.epoch_withdrawn = 0;
+        }
+        let withdrawn = self
+            .epoch_withdrawn
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+        if epoch_limit != 0 {
+            require_gte!(epoch_limit, withdrawn, VaultError::RateLimitExceeded);
+        }
+        self.epoch_withdrawn = withdrawn;
+        Ok(())
+    }
+
+    /// # Check a token outflow
+    /// Same freeze, lock and delay rules as lamports. The epoch limit is counted in lamports
+    /// and cannot meter tokens, so a vault with a limit refuses token withdrawals outright
+    pub fn check_token_outflow(&mut self, now: i64) -> Result<()> {
+        require!(!self.is_frozen(now), VaultError::Frozen);
+        require!(!self.is_locked(now), VaultError::StillLocked);
+        self.check_instant_withdrawal(now)?;
+        require!(self.current_epoch_limit(now) == 0, VaultError::RateLimitExceeded);
+        Ok(())
+    }
+
+    /// # Refuse an instant outflow while a withdrawal delay is set
+    /// Such vaults only pay out through `request_withdraw`/`execute_withdraw`
+    pub fn check_instant_withdrawal(&mut self, now: i64) -> Result<()> {
+        require!(self.current_withdraw_delay(now) == 0, VaultError::WithdrawDelayed);
+        Ok(())
+    }
+
+    /// Current per-epoch limit, applying a pending looser limit once it is due
+    pub fn current_epoch_limit(&mut self, now: i64) -> u64 {
+        if self.pending_limit_at != 0 && now >= self.pending_limit_at {
+            self.epoch_limit = self.pending_epoch_limit;
+            self.pending_epoch_limit = 0;
+            self.pending_limit_at = 0;
+        }
+        self.epoch_limit
+    }
+
+    /// # Change the per-epoch limit
+    /// Tightening applies at once, loosening only after `LIMIT_CHANGE_DELAY`
+    pub fn set_epoch_limit(&mut self, limit: u64, now: i64) {
+        let tighter = limit != 0 && (self.epoch_limit == 0 || limit <= self.epoch_limit);
+        if tighter {
+            self.epoch_limit = limit;
+            self.pending_epoch_limit = 0;
+            self.pending_limit_at = 0;
+        } else {
+            self.pending_epoch_limit = limit;
+            self.pending_limit_at = now + LIMIT_CHANGE_DELAY;
+        }
+    }
+
+    /// Current withdrawal delay, applying a pending shorter delay once it is due
+    pub fn current_withdraw_delay(&mut self, now: i64) -> i64 {
+        if self.pending_delay_at != 0 && now >= self.pending_delay_at {
+            self.withdraw_delay = self.pending_withdraw_delay;
+            self.pending_withdraw_delay = 0;
+            self.pending_delay_at = 0;
+        }
+        self.withdraw_delay
+    }
+
+    /// # Change the withdrawal delay
+    /// Lengthening applies at once, shortening only after the current delay has passed
+    pub fn set_withdraw_delay(&mut self, delay: i64, now: i64) {
+        let current = self.current_withdraw_delay(now);
+        if delay >= current {
+            self.withdraw_delay = delay;
+            self.pending_withdraw_delay = 0;
+            self.pending_delay_at = 0;
+        } else {
+            self.pending_withdraw_delay = delay;
+            self.pending_delay_at = now + current;
+        }
+    }
+
+    /// Guardian in charge at `now`, counting a pending change that is due
+    pub fn guardian_at(&self, now: i64) -> Option<Pubkey> {
+        if self.pending_guardian_at != 0 && now >= self.pending_guardian_at {
+            self.pending_guardian
+        } else {
+            self.guardian
+        }
+    }
+
+    /// # Change the guardian
+    /// Naming the first guardian applies at once, removing or replacing one only after the current
+    /// withdrawal delay, so a stolen key cannot drop the guardian and withdraw before it can cancel
+    pub fn set_guardian(&mut self, guardian: Option<Pubkey>, now: i64) {
+        let current = self.guardian_at(now);
+        if current.is_none() || current == guardian {
+            self.guardian = guardian;
+            self.pending_guardian = None;
+            self.pending_guardian_at = 0;
+        } else {
+            self.guardian = current;
+            self.pending_guardian = guardian;
+            self.pending_guardian_at = now + self.current_withdraw_delay(now);
+        }
+    }
+
+    pub fn track_mint(&mut self, mint: Pubkey) -> Result<()> {
+        if !self.token_mints.contains(&mint) {
+            require_gt!(MAX_TOKEN_MINTS, self.token_mints.len(), VaultError::TooManyMints);
+            self.token_mints.push(mint);
+        }
+        Ok(())
+    }
+
+    pub fn is_guardian(&self, key: &Pubkey) -> bool {
+        self.recovery_guardians.contains(key)
+    }
+
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.total_withdrawn = self
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(VaultError::Overflow)?;
+        Ok(())
+    }
+}
+
+/// Linear vesting paid out of an owner's vault, the unpaid part stays in `VaultState::reserved`
+#[derive(InitSpace)]
+#[account]
+pub struct Stream {
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub recipient: Pubkey,
+    pub start: i64,
+    pub cliff: i64,
+    pub end: i64,
+    pub total: u64,
+    pub withdrawn: u64,
+    pub bump: u8,
+}
+
+impl Stream {
+    /// Amount vested at `now`: nothing before the cliff, linear from `start` to `end`
+    pub fn vested(&self, now: i64) -> u64 {
+        if now < self.cliff {
+            return 0;
+        }
+        if now >= self.end {
+            return self.total;
+        }
+        let elapsed = (now - self.start) as u128;
+        let duration = (self.end - self.start) as u128;
+        // elapsed < duration, so the result is below total and fits in u64
+        (self.total as u128 * elapsed / duration) as u64
+    }
+
+    pub fn withdrawable(&self, now: i64) -> u64 {
+        self.vested(now).saturating_sub(self.withdrawn)
+    }
+}
+
+/// Withdrawal waiting out `VaultState::withdraw_delay`, one per vault
+#[derive(InitSpace)]
+#[account]
+pub struct PendingWithdrawal {
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub amount: u64,
+    pub execute_after: i64,
+    pub bump: u8,
+}
+
+/// Owner offer to hand a vault to `new_owner`, closed once accepted or cancelled
+#[derive(InitSpace)]
+#[account]
+pub struct OwnershipTransfer {
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub new_owner: Pubkey,
+    pub bump: u8,
+}
+
+/// Guardian proposal to move a vault to `new_owner`, closed once executed or cancelled
+#[derive(InitSpace)]
+#[account]
+pub struct Recovery {
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub new_owner: Pubkey,
+    pub new_vault_id: u64,
+    pub proposer: Pubkey,
+    #[max_len(MAX_RECOVERY_GUARDIANS)]
+    pub approvers: Vec<Pubkey>,
+    /// When the proposal was made, `execute_recovery` waits `recovery_delay` from here
+    pub created_at: i64,
+    pub bump: u8,
+}
+
+impl Recovery {
+    /// Guardians may change after the proposal, only current ones count
+    pub fn approval_count(&self, guardians: &[Pubkey]) -> u8 {
+        self.approvers
+            .iter()
+            .filter(|approver| guardians.contains(approver))
+            .count() as u8
+    }
+}
+
+/// Merchant allowed to pull `amount` from an owner's vault once per `period`
+#[derive(InitSpace)]
+#[account]
+pub struct Subscription {
+    pub owner: Pubkey,
+    pub vault_id: u64,
+    pub merchant: Pubkey,
+    pub amount: u64,
+    pub period: i64,
+    pub next_due: i64,
+    pub bump: u8,
+}
+
+impl Subscription {
+    /// Move to the next period, missed periods are not charged retroactively
+    pub fn advance(&mut self, now: i64) {
+        self.next_due += self.period;
+        if self.next_due <= now {
+            self.next_due = now + self.period;
+        }
+    }
+}
+
+/// Per-owner counter, sub-vault ids are handed out as `0..vault_count`
+#[derive(InitSpace)]
+#[account]
+pub struct VaultRegistry {
+    pub owner: Pubkey,
+    pub vault_count: u64,
+    pub bump: u8,
+}
+
+impl VaultRegistry {
+    /// # Hand out the next sub-vault id
+    /// Fills in a registry that `init_if_needed` has just created
+    pub fn next_vault_id(&mut self, owner: Pubkey, bump: u8) -> Result<u64> {
+        if self.owner == Pubkey::default() {
+            self.owner = owner;
+            self.bump = bump;
+        }
+        require_gt!(MAX_VAULTS_PER_OWNER, self.vault_count, VaultError::TooManyVaults);
+        let vault_id = self.vault_count;
+        self.vault_count += 1;
+        Ok(vault_id)
+    }
+}
+
+/// M-of-N team vault, lamports live in the `[b"multisig_vault", multisig]` PDA
+#[derive(InitSpace)]
+#[account]
+pub struct Multisig {
+    pub creator: Pubkey,
+    pub seed: u64,
+    #[max_len(MAX_MULTISIG_MEMBERS)]
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    /// Index of the next proposal, also used as its seed
+    pub proposal_count: u64,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+impl Multisig {
+    pub fn member_index(&self, key: &Pubkey) -> Option<usize> {
+        self.members.iter().position(|member| member == key)
+    }
+}
+
+/// Pending withdrawal from a multisig vault, closed once executed
+#[derive(InitSpace)]
+#[account]
+pub struct Proposal {
+    pub multisig: Pubkey,
+    pub index: u64,
+    pub proposer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    /// Bit `i` is set once `multisig.members[i]` has approved
+    pub approvals: u16,
+    pub bump: u8,
+}
+
+impl Proposal {
+    pub fn approval_count(&self) -> u8 {
+        self.approvals.count_ones() as u8
+    }
+}
+
+/// Shared vault, lamports live in the `[b"pool_vault", pool]` PDA and members hold shares
+#[derive(InitSpace)]
+#[account]
+pub struct Pool {
+    pub creator: Pubkey,
+    pub seed: u64,
+    pub total_shares: u64,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+impl Pool {
+    /// Shares minted for `amount` lamports when the pool holds `assets`, rounded down
+    pub fn shares_for(&self, amount: u64, assets: u64) -> Result<u64> {
+        let shares = amount as u128 * (self.total_shares as u128 + POOL_VIRTUAL_SHARES)
+            / (assets as u128 + POOL_VIRTUAL_LAMPORTS);
+        u64::try_from(shares).map_err(|_| VaultError::Overflow.into())
+    }
+
+    /// Lamports paid out for `shares` when the pool holds `assets`, rounded down
+    pub fn lamports_for(&self, shares: u64, assets: u64) -> u64 {
+        // shares <= total_shares, so the result never exceeds assets
+        (shares as u128 * (assets as u128 + POOL_VIRTUAL_LAMPORTS)
+            / (self.total_shares as u128 + POOL_VIRTUAL_SHARES)) as u64
+    }
+}
+
+/// A member's shares in a pool
+#[derive(InitSpace)]
+#[account]
+pub struct Position {
+    pub pool: Pubkey,
+    pub member: Pubkey,
+    pub shares: u64,
+    pub bump: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(start: i64, cliff: i64, end: i64, total: u64) -> Stream {
+        Stream {
+            owner: Pubkey::default(),
+            vault_id: 0,
+            recipient: Pubkey::default(),
+            start,
+            cliff,
+            end,
+            total,
+            withdrawn: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn stream_vests_nothing_before_the_cliff() {
+        let stream = stream(100, 150, 200, 1_000);
+        assert_eq!(stream.vested(0), 0);
+        assert_eq!(stream.vested(149), 0);
+    }
+
+    #[test]
+    fn stream_vests_linearly_from_start_once_past_the_cliff() {
+        let stream = stream(100, 150, 200, 1_000);
+        assert_eq!(stream.vested(150), 500);
+        assert_eq!(stream.vested(175), 750);
+        assert_eq!(stream.vested(199), 990);
+    }
+
+    #[test]
+    fn stream_vests_everything_at_the_end() {
+        let stream = stream(100, 100, 200, 1_000);
+        assert_eq!(stream.vested(200), 1_000);
+        assert_eq!(stream.vested(i64::MAX), 1_000);
+    }
+
+    #[test]
+    fn stream_vesting_rounds_down_and_does_not_overflow() {
+        let stream = stream(0, 0, 3, u64::MAX);
+        assert_eq!(stream.vested(1), u64::MAX / 3);
+    }
+
+    #[test]
+    fn stream_withdrawable_subtracts_what_was_paid() {
+        let mut stream = stream(0, 0, 100, 1_000);
+        stream.withdrawn = 300;
+        assert_eq!(stream.withdrawable(50), 200);
+        assert_eq!(stream.withdrawable(10), 0);
+    }
+
+    fn pool(total_shares: u64) -> Pool {
+        Pool { creator: Pubkey::default(), seed: 0, total_shares, bump: 0, vault_bump: 0 }
+    }
+
+    #[test]
+    fn first_pool_deposit_mints_virtual_rate() {
+        assert_eq!(pool(0).shares_for(1_000_000, 0).unwrap(), 1_000_000_000);
+    }
+
+    #[test]
+    fn donation_cannot_round_the_next_depositor_to_zero() {
+        // The attacker holds 1 share and donates 10 SOL to the pool vault
+        let pool = pool(1);
+        let assets = 10_000_000_001;
+        let shares = pool.shares_for(1_000_000_000, assets).unwrap();
+        assert!(shares > 0);
+        // Both sides round down, a victim never gets back more than they paid
+        let total = pool.total_shares + shares;
+        let after = Pool { total_shares: total, ..pool };
+        assert!(after.lamports_for(shares, assets + 1_000_000_000) <= 1_000_000_000);
+    }
+
+    #[test]
+    fn shares_round_trip_rounds_down() {
+        let pool = pool(3_000);
+        let shares = pool.shares_for(7, 10).unwrap();
+        let after = Pool { total_shares: pool.total_shares + shares, ..pool };
+        assert!(after.lamports_for(shares, 17) <= 7);
+    }
+
+    #[test]
+    fn lamports_for_all_shares_stays_within_assets() {
+        let pool = pool(1_000_000);
+        assert!(pool.lamports_for(1_000_000, 5_000) <= 5_000);
+    }
+
+    #[test]
+    fn shares_for_rejects_overflow() {
+        assert!(pool(0).shares_for(u64::MAX, 0).is_err());
+    }
+
+    #[test]
+    fn new_recipients_wait_for_the_change_delay() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut state = VaultState { recipients: vec![alice], ..Default::default() };
+        state.set_recipients(vec![alice, bob], 100);
+        assert!(!state.allows_recipient(&bob, 100));
+        assert!(!state.allows_recipient(&bob, 100 + LIMIT_CHANGE_DELAY - 1));
+        assert!(state.allows_recipient(&bob, 100 + LIMIT_CHANGE_DELAY));
+        assert_eq!(state.recipients, vec![alice, bob]);
+    }
+
+    #[test]
+    fn clearing_recipients_is_a_loosening() {
+        let alice = Pubkey::new_unique();
+        let mut state = VaultState { recipients: vec![alice], ..Default::default() };
+        state.set_recipients(Vec::new(), 0);
+        assert!(!state.allows_recipient(&Pubkey::new_unique(), 0));
+        assert!(state.allows_recipient(&Pubkey::new_unique(), LIMIT_CHANGE_DELAY));
+    }
+
+    #[test]
+    fn dropping_recipients_applies_at_once() {
+        let (alice, bob) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut state = VaultState::default();
+        state.set_recipients(vec![alice, bob], 0);
+        assert!(!state.allows_recipient(&Pubkey::new_unique(), 0));
+        state.set_recipients(vec![alice], 0);
+        assert!(!state.allows_recipient(&bob, 0));
+        assert!(state.allows_recipient(&alice, 0));
+    }
+
+    #[test]
+    fn beneficiary_waits_for_the_longer_delay() {
+        let heir = Pubkey::new_unique();
+        let mut state = VaultState { withdraw_delay: 2 * LIMIT_CHANGE_DELAY, ..Default::default() };
+        state.set_beneficiary(Some(heir), MIN_INACTIVITY_PERIOD, 0);
+        assert_eq!(state.current_beneficiary(LIMIT_CHANGE_DELAY), None);
+        assert_eq!(state.current_beneficiary(2 * LIMIT_CHANGE_DELAY), Some(heir));
+        assert_eq!(state.inactivity_period, MIN_INACTIVITY_PERIOD);
+
+        state.set_beneficiary(None, 0, 0);
+        assert_eq!(state.current_beneficiary(0), None);
+    }
+
+    #[test]
+    fn replacing_the_guardian_waits_for_the_delay() {
+        let (old, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut state = VaultState { withdraw_delay: 100, ..Default::default() };
+        state.set_guardian(Some(old), 0);
+        assert_eq!(state.guardian_at(0), Some(old));
+
+        state.set_guardian(None, 10);
+        assert_eq!(state.guardian_at(109), Some(old));
+        assert_eq!(state.guardian_at(110), None);
+
+        state.set_guardian(Some(new), 20);
+        assert_eq!(state.guardian_at(119), Some(old));
+        assert_eq!(state.guardian_at(120), Some(new));
+    }
+}
@@ -0,0 +1,140 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import {
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  StakeProgram,
+  SYSVAR_STAKE_HISTORY_PUBKEY,
+} from "@solana/web3.js";
+import { expect } from "chai";
+import { BlueshiftAnchorVault } from "../target/types/blueshift_anchor_vault";
+
+// 质押全流程：create_stake -> deactivate_stake -> withdraw_stake，质押 PDA 事先被人转入了少量 lamports。
+// 在同一个 epoch 里委托又取消的质押从未生效，无需等待冷却即可取回；
+// 本地验证器开头几个 epoch 很短，如果中间跨了 epoch，就重试到冷却结束
+describe("stake", () => {
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+
+  const program = anchor.workspace.blueshiftAnchorVault as Program<BlueshiftAnchorVault>;
+  const owner = provider.wallet.publicKey;
+
+  const pda = (...seeds: Buffer[]) => PublicKey.findProgramAddressSync(seeds, program.programId)[0];
+  const configPda = pda(Buffer.from("config"));
+  const registryPda = pda(Buffer.from("registry"), owner.toBuffer());
+
+  let vaultId: BN;
+  let vault: PublicKey;
+  let stakeAccount: PublicKey;
+
+  before(async () => {
+    // The provider wallet deploys the program, so it is the upgrade authority
+    if (!(await program.account.config.fetchNullable(configPda))) {
+      const [programData] = PublicKey.findProgramAddressSync(
+        [program.programId.toBuffer()],
+        new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111")
+      );
+      await program.methods
+        .initializeConfig(0, owner)
+        .accountsPartial({ admin: owner, config: configPda, programData })
+        .rpc();
+    }
+
+    const registry = await program.account.vaultRegistry.fetchNullable(registryPda);
+    vaultId = registry ? registry.vaultCount : new BN(0);
+    vault = pda(Buffer.from("vault"), owner.toBuffer(), vaultId.toArrayLike(Buffer, "le", 8));
+    stakeAccount = pda(Buffer.from("stake"), vault.toBuffer());
+
+    await program.methods.openVault("stake").accountsPartial({ signer: owner, registry: registryPda, vault }).rpc();
+    const config = await program.account.config.fetch(configPda);
+    await program.methods
+      .deposit(vaultId, new BN(2 * LAMPORTS_PER_SOL), null)
+      .accountsPartial({ signer: owner, vault, config: configPda, treasury: config.treasury })
+      .rpc();
+  });
+
+  it("delegates, deactivates and withdraws back into the vault", async () => {
+    const { current } = await provider.connection.getVoteAccounts();
+    const voteAccount = new PublicKey(current[0].votePubkey);
+    const before = await provider.connection.getBalance(vault);
+    const amount = LAMPORTS_PER_SOL;
+
+    // Anyone can send lamports to the stake PDA ahead of time, create_stake must still work
+    const prefund = 1_000_000;
+    await provider.sendAndConfirm(
+      new anchor.web3.Transaction().add(
+        anchor.web3.SystemProgram.transfer({ fromPubkey: owner, toPubkey: stakeAccount, lamports: prefund })
+      )
+    );
+
+    await program.methods
+      .createStake(vaultId, new BN(amount))
+      .accountsPartial({
+        signer: owner,
+        vault,
+        stakeAccount,
+        voteAccount,
+        stakeConfig: new PublicKey("StakeConfig11111111111111111111111111111111"),
+        stakeHistory: SYSVAR_STAKE_HISTORY_PUBKEY,
+        stakeProgram: StakeProgram.programId,
+      })
+      .rpc();
+
+    const stakeInfo = await provider.connection.getAccountInfo(stakeAccount);
+    expect(stakeInfo.owner.equals(StakeProgram.programId)).to.be.true;
+    expect(stakeInfo.lamports).to.equal(amount + prefund);
+    expect(await provider.connection.getBalance(vault)).to.equal(before - amount);
+
+    await program.methods
+      .deactivateStake(vaultId)
+      .accountsPartial({ signer: owner, owner, vault, stakeAccount, stakeProgram: StakeProgram.programId })
+      .rpc();
+
+    for (let attempt = 0; ; attempt++) {
+      try {
+        await program.methods
+          .withdrawStake(vaultId)
+          .accountsPartial({
+            owner,
+            vault,
+            destination: vault,
+            stakeAccount,
+            stakeHistory: SYSVAR_STAKE_HISTORY_PUBKEY,
+            stakeProgram: StakeProgram.programId,
+          })
+          .rpc();
+        break;
+      } catch (err) {
+        if (attempt >= 60) throw err;
+        await new Promise((resolve) => setTimeout(resolve, 5_000));
+      }
+    }
+
+    expect(await provider.connection.getAccountInfo(stakeAccount)).to.be.null;
+    // Rewards land on top if the stake stayed active across an epoch
+    expect(await provider.connection.getBalance(vault)).to.be.at.least(before);
+  });
+
+  it("refuses to stake lamports the vault cannot spend", async () => {
+    const { current } = await provider.connection.getVoteAccounts();
+    const balance = await provider.connection.getBalance(vault);
+
+    try {
+      await program.methods
+        .createStake(vaultId, new BN(balance + 1))
+        .accountsPartial({
+          signer: owner,
+          vault,
+          stakeAccount,
+          voteAccount: new PublicKey(current[0].votePubkey),
+          stakeConfig: new PublicKey("StakeConfig11111111111111111111111111111111"),
+          stakeHistory: SYSVAR_STAKE_HISTORY_PUBKEY,
+          stakeProgram: StakeProgram.programId,
+        })
+        .rpc();
+      expect.fail("create_stake should have failed");
+    } catch (err) {
+      expect(err).to.be.instanceOf(anchor.AnchorError);
+    }
+  });
+});
@@ -19,6 +19,8 @@ export const MakeForm: FC = () => {
     const [depositAmount, setDepositAmount] = useState('');
     const [tokenMintA, setTokenMintA] = useState('');
     const [tokenMintB, setTokenMintB] = useState('');
+    const [expiresAt, setExpiresAt] = useState('');
+    const [taker, setTaker] = useState('');
 
     const handleMake = async () => {
         if (!wallet || !seed || !receiveAmount || !depositAmount || !tokenMintA || !tokenMintB) return;
@@ -29,6 +31,10 @@ export const MakeForm: FC = () => {
             const seedBN = new BN(seed);
             const receiveBN = new BN(receiveAmount);
             const depositBN = new BN(depositAmount);
+            // 留空表示永不过期
+            const expiresAtBN = new BN(expiresAt ? Math.floor(new Date(expiresAt).getTime() / 1000) : 0);
+            // 留空表示任何人都可以 Take
+            const takerKey = taker ? new web3.PublicKey(taker) : null;
 
             const mintA = new web3.PublicKey(tokenMintA);
             const mintB = new web3.PublicKey(tokenMintB);
@@ -55,7 +61,7 @@ export const MakeForm: FC = () => {
             );
 
             const tx = await program.methods
-                .make(seedBN, receiveBN, depositBN)
+                .make(seedBN, receiveBN, depositBN, expiresAtBN, takerKey)
                 .accounts({
                     maker: wallet.publicKey,
                     escrow: escrowPda,
@@ -120,6 +126,22 @@ export const MakeForm: FC = () => {
                     onChange={(e) => setReceiveAmount(e.target.value)}
                 />
             </div>
+            <div className="input-group">
+                <input
+                    type="datetime-local"
+                    title="过期时间 (留空表示永不过期)"
+                    value={expiresAt}
+                    onChange={(e) => setExpiresAt(e.target.value)}
+                />
+            </div>
+            <div className="input-group">
+                <input
+                    type="text"
+                    placeholder="指定 Taker (OTC 对手方地址，留空表示任何人)"
+                    value={taker}
+                    onChange={(e) => setTaker(e.target.value)}
+                />
+            </div>
             <button onClick={handleMake} disabled={!wallet}>
                 创建担保订单
             </button>
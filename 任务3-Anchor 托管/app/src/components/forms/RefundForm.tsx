@@ -2,6 +2,7 @@
 import { FC, useState } from 'react';
 import { useConnection, useAnchorWallet } from '@solana/wallet-adapter-react';
 import * as web3 from '@solana/web3.js';
+import { BN } from '@coral-xyz/anchor';
 import { getProgram } from '../../utils/anchor';
 import {
     getAssociatedTokenAddressSync,
@@ -9,6 +10,10 @@ import {
     ASSOCIATED_TOKEN_PROGRAM_ID
 } from "@solana/spl-token";
 
+// 加入 expires_at 和 taker 之前创建的 Escrow：1 字节 discriminator + seed + maker + mint_a + mint_b + receive + bump，
+// make 当时按 INIT_SPACE + 8 分配空间
+const LEGACY_ESCROW_LEN = 8 + 8 + 32 * 3 + 8 + 1;
+
 export const RefundForm: FC = () => {
     const { connection } = useConnection();
     const wallet = useAnchorWallet();
@@ -21,10 +26,13 @@ export const RefundForm: FC = () => {
             const program = getProgram(connection, wallet);
             const escrowPubkey = new web3.PublicKey(escrowAddress);
 
-            // Fetch Escrow Account Data
-            const escrowAccount = await program.account.escrow.fetch(escrowPubkey);
-
-            const mintA = escrowAccount.mintA;
+            // 旧布局的 Escrow 无法用 IDL 解码，直接从原始数据里读出 seed 和 mint_a
+            const info = await connection.getAccountInfo(escrowPubkey);
+            if (!info) throw new Error('Escrow 账户不存在');
+            const legacy = info.data.length === LEGACY_ESCROW_LEN;
+            const mintA = legacy
+                ? new web3.PublicKey(info.data.subarray(41, 73))
+                : (await program.account.escrow.fetch(escrowPubkey)).mintA;
 
             // Re-derive Vault PDA
             const vault = getAssociatedTokenAddressSync(
@@ -39,19 +47,25 @@ export const RefundForm: FC = () => {
                 wallet.publicKey
             );
 
-            const tx = await program.methods
-                .refund()
-                .accounts({
-                    maker: wallet.publicKey,
-                    escrow: escrowPubkey,
-                    mintA: mintA,
-                    vault: vault,
-                    makerAtaA: makerAtaA,
-                    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
-                    tokenProgram: TOKEN_PROGRAM_ID,
-                    systemProgram: web3.SystemProgram.programId,
-                })
-                .rpc();
+            const accounts = {
+                maker: wallet.publicKey,
+                escrow: escrowPubkey,
+                mintA: mintA,
+                vault: vault,
+                makerAtaA: makerAtaA,
+                associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+                tokenProgram: TOKEN_PROGRAM_ID,
+                systemProgram: web3.SystemProgram.programId,
+            };
+            const tx = legacy
+                ? await program.methods
+                    .refundLegacy(new BN(info.data.subarray(1, 9), 'le'))
+                    .accounts(accounts)
+                    .rpc()
+                : await program.methods
+                    .refund()
+                    .accounts(accounts)
+                    .rpc();
 
             console.log('Transaction signature', tx);
             alert(`退款成功! 交易哈希: ${tx}`);
@@ -2,6 +2,7 @@
 import { FC, useState } from 'react';
 import { useConnection, useAnchorWallet } from '@solana/wallet-adapter-react';
 import * as web3 from '@solana/web3.js';
+import { BN } from '@coral-xyz/anchor';
 import { getProgram } from '../../utils/anchor';
 import {
     getAssociatedTokenAddressSync,
@@ -13,6 +14,9 @@ export const TakeForm: FC = () => {
     const { connection } = useConnection();
     const wallet = useAnchorWallet();
     const [escrowAddress, setEscrowAddress] = useState('');
+    const [amountB, setAmountB] = useState('');
+    const [minAmountA, setMinAmountA] = useState('');
+    const [referrer, setReferrer] = useState('');
 
     const handleTake = async () => {
         if (!wallet || !escrowAddress) return;
@@ -34,6 +38,13 @@ export const TakeForm: FC = () => {
                 true
             );
 
+            // 留空则全部成交；最少收到的 Token A 留空时取当前报价，Maker 在此期间 update 条款会让交易失败
+            const receive = escrowAccount.receive as BN;
+            const amountBBN = amountB ? new BN(amountB) : receive;
+            const vaultBalance = await connection.getTokenAccountBalance(vault);
+            const quoteA = new BN(vaultBalance.value.amount).mul(amountBBN).div(receive);
+            const minAmountABN = minAmountA ? new BN(minAmountA) : quoteA;
+
             const takerAtaA = getAssociatedTokenAddressSync(
                 mintA,
                 wallet.publicKey
@@ -49,8 +60,26 @@ export const TakeForm: FC = () => {
                 maker
             );
 
+            // 手续费配置：管理员需要先 initialize_config，手续费打到国库的 Token B ATA（不存在时由 Taker 创建）；
+            // 手续费为 0 时不传国库 ATA，Taker 不用为它付租金
+            const [configPda] = web3.PublicKey.findProgramAddressSync(
+                [Buffer.from("config")],
+                program.programId
+            );
+            const configAccount = await program.account.config.fetch(configPda);
+            const treasury = configAccount.treasury as web3.PublicKey;
+
+            const treasuryAtaB = configAccount.feeBps > 0
+                ? getAssociatedTokenAddressSync(mintB, treasury)
+                : null;
+
+            // 可选的推荐人：分走一部分手续费，其 Token B ATA 必须已经存在；没有白名单，填自己就相当于手续费折扣
+            const referrerAtaB = referrer
+                ? getAssociatedTokenAddressSync(mintB, new web3.PublicKey(referrer))
+                : null;
+
             const tx = await program.methods
-                .take()
+                .take(amountBBN, minAmountABN)
                 .accounts({
                     taker: wallet.publicKey,
                     maker: maker,
@@ -61,6 +90,10 @@ export const TakeForm: FC = () => {
                     takerAtaA: takerAtaA,
                     takerAtaB: takerAtaB,
                     makerAtaB: makerAtaB,
+                    treasury: treasury,
+                    treasuryAtaB: treasuryAtaB,
+                    referrerAtaB: referrerAtaB,
+                    config: configPda,
                     associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
                     tokenProgram: TOKEN_PROGRAM_ID,
                     systemProgram: web3.SystemProgram.programId,
@@ -86,6 +119,30 @@ export const TakeForm: FC = () => {
                     onChange={(e) => setEscrowAddress(e.target.value)}
                 />
             </div>
+            <div className="input-group">
+                <input
+                    type="number"
+                    placeholder="支付数量 (Amount B，留空表示全部成交)"
+                    value={amountB}
+                    onChange={(e) => setAmountB(e.target.value)}
+                />
+            </div>
+            <div className="input-group">
+                <input
+                    type="number"
+                    placeholder="最少收到 (Min Amount A，留空表示当前报价)"
+                    value={minAmountA}
+                    onChange={(e) => setMinAmountA(e.target.value)}
+                />
+            </div>
+            <div className="input-group">
+                <input
+                    type="text"
+                    placeholder="推荐人地址 (Referrer，可选)"
+                    value={referrer}
+                    onChange={(e) => setReferrer(e.target.value)}
+                />
+            </div>
             <button onClick={handleTake} disabled={!wallet}>
                 完成订单 (Take)
             </button>
@@ -10,4 +10,24 @@ pub enum EscrowError {
     InvalidMintA,
     #[msg("Invalid mint b")]
     InvalidMintB,
+    #[msg("Invalid expiry")]
+    InvalidExpiry,
+    #[msg("Escrow has expired")]
+    Expired,
+    #[msg("Escrow has not expired yet")]
+    NotExpired,
+    #[msg("Invalid taker")]
+    InvalidTaker,
+    #[msg("Fee too high")]
+    FeeTooHigh,
+    #[msg("Invalid treasury")]
+    InvalidTreasury,
+    #[msg("Invalid referrer")]
+    InvalidReferrer,
+    #[msg("Unauthorized")]
+    Unauthorized,
+    #[msg("Token A received is below the minimum")]
+    SlippageExceeded,
+    #[msg("Not an escrow in the legacy layout")]
+    InvalidEscrow,
 }
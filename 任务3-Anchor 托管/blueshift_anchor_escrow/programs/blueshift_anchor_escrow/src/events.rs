@@ -0,0 +1,15 @@
+use anchor_lang::prelude::*;
+
+/// Emitted by every `take`, full or partial
+#[event]
+pub struct EscrowTaken {
+    pub escrow: Pubkey,
+    pub taker: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    /// Token B that reached the maker after fees
+    pub maker_amount: u64,
+    pub protocol_fee: u64,
+    pub referrer_fee: u64,
+    pub referrer: Option<Pubkey>,
+}
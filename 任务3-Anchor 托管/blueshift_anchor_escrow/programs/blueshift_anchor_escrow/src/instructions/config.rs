@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use crate::program::BlueshiftAnchorEscrow;
+use crate::state::{Config, MAX_FEE_BPS};
+use crate::errors::EscrowError;
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    #[account(
+        init,
+        payer = admin,
+        space = Config::INIT_SPACE + 8,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Account<'info, Config>,
+    // The first admin is whoever can upgrade the program
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, BlueshiftAnchorEscrow>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(admin.key()) @ EscrowError::Unauthorized)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ EscrowError::Unauthorized,
+    )]
+    pub config: Account<'info, Config>,
+}
+
+pub fn initialize_handler(ctx: Context<InitializeConfig>, fee_bps: u16, referral_share_bps: u16, treasury: Pubkey) -> Result<()> {
+    require_gte!(MAX_FEE_BPS, fee_bps, EscrowError::FeeTooHigh);
+    require_gte!(10_000, referral_share_bps, EscrowError::FeeTooHigh);
+    ctx.accounts.config.set_inner(Config {
+        admin: ctx.accounts.admin.key(),
+        fee_bps,
+        referral_share_bps,
+        treasury,
+        bump: ctx.bumps.config,
+    });
+    Ok(())
+}
+
+pub fn set_fee_handler(ctx: Context<UpdateConfig>, fee_bps: u16, referral_share_bps: u16, treasury: Pubkey) -> Result<()> {
+    require_gte!(MAX_FEE_BPS, fee_bps, EscrowError::FeeTooHigh);
+    require_gte!(10_000, referral_share_bps, EscrowError::FeeTooHigh);
+    let config = &mut ctx.accounts.config;
+    config.fee_bps = fee_bps;
+    config.referral_share_bps = referral_share_bps;
+    config.treasury = treasury;
+    Ok(())
+}
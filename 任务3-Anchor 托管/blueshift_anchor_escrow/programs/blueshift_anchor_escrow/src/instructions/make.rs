@@ -49,13 +49,15 @@ pub struct Make<'info> {
 
 impl<'info> Make<'info> {
     /// # Create the Escrow
-    pub fn populate_escrow(&mut self, seed: u64, amount: u64, bump: u8) -> Result<()> {
+    pub fn populate_escrow(&mut self, seed: u64, amount: u64, expires_at: i64, taker: Option<Pubkey>, bump: u8) -> Result<()> {
         self.escrow.set_inner(Escrow {
             seed,
             maker: self.maker.key(),
             mint_a: self.mint_a.key(),
             mint_b: self.mint_b.key(),
             receive: amount,
+            expires_at,
+            taker,
             bump,
         });
         Ok(())
@@ -79,12 +81,16 @@ impl<'info> Make<'info> {
     }
 }
 
-pub fn handler(ctx: Context<Make>, seed: u64, receive: u64, amount: u64) -> Result<()> {
+pub fn handler(ctx: Context<Make>, seed: u64, receive: u64, amount: u64, expires_at: i64, taker: Option<Pubkey>) -> Result<()> {
     // Validate the amount
     require_gt!(receive, 0, EscrowError::InvalidAmount);
     require_gt!(amount, 0, EscrowError::InvalidAmount);
+    // Validate the expiry
+    if expires_at != 0 {
+        require_gt!(expires_at, Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+    }
     // Save the Escrow Data 创建合同
-    ctx.accounts.populate_escrow(seed, receive, ctx.bumps.escrow)?;
+    ctx.accounts.populate_escrow(seed, receive, expires_at, taker, ctx.bumps.escrow)?;
     // Deposit Tokens
     ctx.accounts.deposit_tokens(amount)?;
     Ok(())
@@ -1,7 +1,15 @@
+pub mod config;
 pub mod make;
 pub mod take;
 pub mod refund;
+pub mod refund_legacy;
+pub mod close_expired;
+pub mod update;
 
+pub use config::*;
 pub use make::*;
 pub use take::*;
 pub use refund::*;
+pub use refund_legacy::*;
+pub use close_expired::*;
+pub use update::*;
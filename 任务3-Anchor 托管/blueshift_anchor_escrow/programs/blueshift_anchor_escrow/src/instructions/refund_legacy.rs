@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked, CloseAccount, close_account};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::LegacyEscrow;
+use crate::errors::EscrowError;
+
+#[derive(Accounts)]
+#[instruction(seed: u64)]
+pub struct RefundLegacy<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    /// CHECK: escrow in the layout before `expires_at` and `taker`, parsed by `LegacyEscrow::try_from_bytes`
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"escrow", maker.key().as_ref(), seed.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub escrow: UncheckedAccount<'info>,
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RefundLegacy<'info> {
+    pub fn withdraw_and_close_vault(&mut self, seed: u64, bump: u8) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &seed.to_le_bytes()[..],
+            &[bump],
+        ]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            self.vault.amount,
+            self.mint_a.decimals,
+        )?;
+
+        close_account(CpiContext::new_with_signer(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.vault.to_account_info(),
+                authority: self.escrow.to_account_info(),
+                destination: self.maker.to_account_info(),
+            },
+            &signer_seeds,
+        ))?;
+
+        Ok(())
+    }
+
+    /// # Close the escrow account by hand, it cannot be loaded as `Account<Escrow>`
+    pub fn close_escrow(&mut self) -> Result<()> {
+        let escrow = self.escrow.to_account_info();
+        **self.maker.to_account_info().try_borrow_mut_lamports()? += escrow.lamports();
+        **escrow.try_borrow_mut_lamports()? = 0;
+        escrow.assign(&System::id());
+        escrow.resize(0)?;
+        Ok(())
+    }
+}
+
+pub fn handler(ctx: Context<RefundLegacy>, seed: u64) -> Result<()> {
+    let escrow = LegacyEscrow::try_from_bytes(&ctx.accounts.escrow.try_borrow_data()?)?;
+    require_keys_eq!(escrow.maker, ctx.accounts.maker.key(), EscrowError::InvalidMaker);
+    require_keys_eq!(escrow.mint_a, ctx.accounts.mint_a.key(), EscrowError::InvalidMintA);
+
+    ctx.accounts.withdraw_and_close_vault(seed, ctx.bumps.escrow)?;
+    ctx.accounts.close_escrow()?;
+    Ok(())
+}
@@ -1,8 +1,9 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked, CloseAccount, close_account};
 use anchor_spl::associated_token::AssociatedToken;
-use crate::state::Escrow;
+use crate::state::{Config, Escrow};
 use crate::errors::EscrowError;
+use crate::events::EscrowTaken;
 
 #[derive(Accounts)]
 pub struct Take<'info> {
@@ -12,12 +13,12 @@ pub struct Take<'info> {
     pub maker: SystemAccount<'info>,
     #[account(
         mut,
-        close = maker,
         seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
         bump = escrow.bump,
         has_one = maker @ EscrowError::InvalidMaker,
         has_one = mint_a @ EscrowError::InvalidMintA,
         has_one = mint_b @ EscrowError::InvalidMintB,
+        constraint = escrow.allows_taker(&taker.key()) @ EscrowError::InvalidTaker,
     )]
     pub escrow: Box<Account<'info, Escrow>>,
     /// Token Accounts
@@ -59,6 +60,34 @@ pub struct Take<'info> {
         associated_token::token_program = token_program
     )]
     pub maker_ata_b: Box<InterfaceAccount<'info, TokenAccount>>,
+    #[account(
+        address = config.treasury @ EscrowError::InvalidTreasury,
+    )]
+    pub treasury: SystemAccount<'info>,
+    /// Only needed when this take pays a protocol fee, created on the first such take of a new Token B.
+    /// Left out while `fee_bps` is 0, so takers do not pay rent for an account that never receives anything
+    #[account(
+        init_if_needed,
+        payer = taker,
+        associated_token::mint = mint_b,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program
+    )]
+    pub treasury_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    /// Optional frontend referrer, gets `referral_share_bps` of the fee.
+    /// Referrers are not whitelisted and a taker can name any wallet, its own included,
+    /// so the referral share is effectively a fee discount open to every taker
+    #[account(
+        mut,
+        token::mint = mint_b,
+        token::token_program = token_program,
+    )]
+    pub referrer_ata_b: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
     /// Programs
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
@@ -66,23 +95,40 @@ pub struct Take<'info> {
 }
 
 impl<'info> Take<'info> {
-    pub fn transfer_to_maker(&mut self) -> Result<()> {
+    /// # Pay Token B from the taker, skimming the protocol and referral fees
+    /// Returns the (maker, treasury, referrer) split
+    pub fn transfer_to_maker(&mut self, amount_b: u64) -> Result<(u64, u64, u64)> {
+        let (maker_amount, protocol_fee, referrer_fee) = self.config.split(amount_b, self.referrer_ata_b.is_some());
+
+        self.pay_b(self.maker_ata_b.to_account_info(), maker_amount)?;
+        if protocol_fee > 0 {
+            let treasury_ata_b = self.treasury_ata_b.as_ref().ok_or(EscrowError::InvalidTreasury)?;
+            self.pay_b(treasury_ata_b.to_account_info(), protocol_fee)?;
+        }
+        if let Some(referrer_ata_b) = &self.referrer_ata_b {
+            if referrer_fee > 0 {
+                self.pay_b(referrer_ata_b.to_account_info(), referrer_fee)?;
+            }
+        }
+        Ok((maker_amount, protocol_fee, referrer_fee))
+    }
+    fn pay_b(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
         transfer_checked(
             CpiContext::new(
                 self.token_program.to_account_info(),
                 TransferChecked {
                     from: self.taker_ata_b.to_account_info(),
-                    to: self.maker_ata_b.to_account_info(),
+                    to,
                     mint: self.mint_b.to_account_info(),
                     authority: self.taker.to_account_info(),
                 },
             ),
-            self.escrow.receive,
+            amount,
             self.mint_b.decimals,
         )?;
         Ok(())
     }
-    pub fn withdraw_and_close_vault(&mut self) -> Result<()> {
+    pub fn withdraw_from_vault(&mut self, amount_a: u64) -> Result<()> {
         // Create the signer seeds for the Vault
         let signer_seeds: [&[&[u8]]; 1] = [&[
             b"escrow",
@@ -102,9 +148,18 @@ impl<'info> Take<'info> {
                 },
                 &signer_seeds,
             ),
-            self.vault.amount,
+            amount_a,
             self.mint_a.decimals,
         )?;
+        Ok(())
+    }
+    pub fn close_vault(&mut self) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
         // Close the Vault
         close_account(CpiContext::new_with_signer(
             self.token_program.to_account_info(),
@@ -119,10 +174,40 @@ impl<'info> Take<'info> {
     }
 }
 
-pub fn handler(ctx: Context<Take>) -> Result<()> {
-    // Transfer Token B to Maker
-    ctx.accounts.transfer_to_maker()?;
-    // Withdraw and close the Vault
-    ctx.accounts.withdraw_and_close_vault()?;
+pub fn handler(ctx: Context<Take>, amount_b: u64, min_amount_a: u64) -> Result<()> {
+    require!(
+        !ctx.accounts.escrow.is_expired(Clock::get()?.unix_timestamp),
+        EscrowError::Expired
+    );
+    // Validate the amount, at most what is still open
+    require_gt!(amount_b, 0, EscrowError::InvalidAmount);
+    require_gte!(ctx.accounts.escrow.receive, amount_b, EscrowError::InvalidAmount);
+    let amount_a = ctx.accounts.escrow.amount_a_for(ctx.accounts.vault.amount, amount_b);
+    require_gt!(amount_a, 0, EscrowError::InvalidAmount);
+    // The maker can `update` the terms between quote and execution
+    require_gte!(amount_a, min_amount_a, EscrowError::SlippageExceeded);
+
+    // Transfer Token B to Maker, minus fees
+    let (maker_amount, protocol_fee, referrer_fee) = ctx.accounts.transfer_to_maker(amount_b)?;
+    // Withdraw Token A from the Vault
+    ctx.accounts.withdraw_from_vault(amount_a)?;
+
+    emit!(EscrowTaken {
+        escrow: ctx.accounts.escrow.key(),
+        taker: ctx.accounts.taker.key(),
+        amount_a,
+        amount_b,
+        maker_amount,
+        protocol_fee,
+        referrer_fee,
+        referrer: ctx.accounts.referrer_ata_b.as_ref().map(|referrer_ata_b| referrer_ata_b.owner),
+    });
+
+    // Fully filled: close the Vault and the Escrow, otherwise leave the rest open
+    ctx.accounts.escrow.receive -= amount_b;
+    if ctx.accounts.escrow.receive == 0 {
+        ctx.accounts.close_vault()?;
+        ctx.accounts.escrow.close(ctx.accounts.maker.to_account_info())?;
+    }
     Ok(())
 }
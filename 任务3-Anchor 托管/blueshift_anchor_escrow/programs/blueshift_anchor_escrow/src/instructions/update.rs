@@ -0,0 +1,127 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface, TransferChecked, transfer_checked};
+use anchor_spl::associated_token::AssociatedToken;
+use crate::state::Escrow;
+use crate::errors::EscrowError;
+
+#[derive(Accounts)]
+pub struct Update<'info> {
+    #[account(mut)]
+    pub maker: Signer<'info>,
+    // Same checks as Refund, the Escrow just stays open
+    #[account(
+        mut,
+        seeds = [b"escrow", maker.key().as_ref(), escrow.seed.to_le_bytes().as_ref()],
+        bump = escrow.bump,
+        has_one = maker @ EscrowError::InvalidMaker,
+        has_one = mint_a @ EscrowError::InvalidMintA,
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        mint::token_program = token_program
+    )]
+    pub mint_a: InterfaceAccount<'info, Mint>,
+    #[account(
+        mut,
+        associated_token::mint = mint_a,
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program
+    )]
+    pub vault: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        init_if_needed,
+        payer = maker,
+        associated_token::mint = mint_a,
+        associated_token::authority = maker,
+        associated_token::token_program = token_program
+    )]
+    pub maker_ata_a: InterfaceAccount<'info, TokenAccount>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> Update<'info> {
+    /// # Deposit more tokens
+    pub fn deposit_tokens(&self, amount: u64) -> Result<()> {
+        transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    to: self.vault.to_account_info(),
+                    authority: self.maker.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint_a.decimals,
+        )?;
+        Ok(())
+    }
+    /// # Withdraw part of the tokens, the Vault stays open
+    pub fn withdraw_tokens(&self, amount: u64) -> Result<()> {
+        let signer_seeds: [&[&[u8]]; 1] = [&[
+            b"escrow",
+            self.maker.to_account_info().key.as_ref(),
+            &self.escrow.seed.to_le_bytes()[..],
+            &[self.escrow.bump],
+        ]];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                TransferChecked {
+                    from: self.vault.to_account_info(),
+                    to: self.maker_ata_a.to_account_info(),
+                    mint: self.mint_a.to_account_info(),
+                    authority: self.escrow.to_account_info(),
+                },
+                &signer_seeds,
+            ),
+            amount,
+            self.mint_a.decimals,
+        )?;
+        Ok(())
+    }
+}
+
+pub fn handler(
+    ctx: Context<Update>,
+    receive: Option<u64>,
+    deposit: u64,
+    withdraw: u64,
+    expires_at: Option<i64>,
+) -> Result<()> {
+    if let Some(receive) = receive {
+        require_gt!(receive, 0, EscrowError::InvalidAmount);
+        ctx.accounts.escrow.receive = receive;
+    }
+
+    if let Some(expires_at) = expires_at {
+        // Only extend: a later time, or 0 to drop the expiry
+        let current = ctx.accounts.escrow.expires_at;
+        if expires_at != 0 {
+            require!(current != 0 && expires_at >= current, EscrowError::InvalidExpiry);
+            require_gt!(expires_at, Clock::get()?.unix_timestamp, EscrowError::InvalidExpiry);
+        }
+        ctx.accounts.escrow.expires_at = expires_at;
+    }
+
+    // Something has to stay in the Vault, closing the offer is what refund is for
+    let remaining = ctx
+        .accounts
+        .vault
+        .amount
+        .checked_add(deposit)
+        .ok_or(EscrowError::InvalidAmount)?;
+    require_gt!(remaining, withdraw, EscrowError::InvalidAmount);
+
+    if deposit > 0 {
+        ctx.accounts.deposit_tokens(deposit)?;
+    }
+    if withdraw > 0 {
+        ctx.accounts.withdraw_tokens(withdraw)?;
+    }
+    Ok(())
+}
@@ -3,6 +3,7 @@ use anchor_lang::prelude::*;
 mod state;
 mod errors;
 mod instructions;
+pub mod events;
 
 use instructions::*;
 
@@ -17,16 +18,21 @@ pub mod blueshift_anchor_escrow {
         ctx: Context<Make>, 
         seed: u64,    // 唯一种子：用于生成唯一的 Escrow 账户地址，防止同一用户创建重复订单
         receive: u64, // 期望接收数量：Maker 想要交换得到的 Token B 的数量
-        amount: u64   // 存款数量：Maker 存入 Vault 的 Token A 的数量
+        amount: u64,  // 存款数量：Maker 存入 Vault 的 Token A 的数量
+        expires_at: i64, // 过期时间：之后不能再 take，任何人都可以 close_expired；0 表示永不过期
+        taker: Option<Pubkey> // 指定 Taker：OTC 私下约定的对手方，设置后其他人无法 take；None 表示任何人都可以
     ) -> Result<()> {
-        instructions::make::handler(ctx, seed, receive, amount)
+        instructions::make::handler(ctx, seed, receive, amount, expires_at, taker)
     }
 
     #[instruction(discriminator = 1)]
-    pub fn take(ctx: Context<Take>) -> Result<()> {
-        // take 指令只需要 Context 上下文，因为它所需要的所有账户（Signer, Escrow, Vault, Token Accounts）
-        // 都已经包含在 Context<Take> 里的结构体定义中了，不需要额外的数值参数。
-        instructions::take::handler(ctx)
+    pub fn take(
+        ctx: Context<Take>,
+        amount_b: u64,    // 支付数量：Taker 这次付出的 Token B，可以只成交一部分，最多为 escrow.receive；手续费从中扣除
+        min_amount_a: u64 // 最少收到的 Token A：Maker 可能在报价后 update 条款，低于此数量直接失败
+    ) -> Result<()> {
+        // 按比例从 Vault 取出 Token A（向下取整，对 Maker 有利），全部成交后才关闭 Vault 和 Escrow
+        instructions::take::handler(ctx, amount_b, min_amount_a)
     }
 
     #[instruction(discriminator = 2)]
@@ -35,4 +41,46 @@ pub mod blueshift_anchor_escrow {
         // 它只需要 Maker 签名确认，以及对应的 Escrow 和 Vault 账户即可执行退款逻辑。
         instructions::refund::handler(ctx)
     }
+
+    #[instruction(discriminator = 3)]
+    pub fn close_expired(ctx: Context<CloseExpired>) -> Result<()> {
+        // 无需许可：任何人都可以清理过期的 Escrow，Token A 退回 maker_ata_a，租金退给 Maker
+        instructions::close_expired::handler(ctx)
+    }
+
+    #[instruction(discriminator = 4)]
+    pub fn initialize_config(
+        ctx: Context<InitializeConfig>,
+        fee_bps: u16,            // 协议手续费：从 Token B 中扣除，最多 MAX_FEE_BPS
+        referral_share_bps: u16, // 推荐人分成：手续费中分给推荐人的比例；推荐人没有白名单，Taker 可以填自己，相当于手续费折扣
+        treasury: Pubkey         // 国库：接收手续费的 Token B 账户所有者
+    ) -> Result<()> {
+        instructions::config::initialize_handler(ctx, fee_bps, referral_share_bps, treasury)
+    }
+
+    #[instruction(discriminator = 5)]
+    pub fn set_fee(ctx: Context<UpdateConfig>, fee_bps: u16, referral_share_bps: u16, treasury: Pubkey) -> Result<()> {
+        instructions::config::set_fee_handler(ctx, fee_bps, referral_share_bps, treasury)
+    }
+
+    #[instruction(discriminator = 6)]
+    pub fn update(
+        ctx: Context<Update>,
+        receive: Option<u64>,    // 新的期望接收数量：None 表示不变
+        deposit: u64,            // 追加存入 Vault 的 Token A，0 表示不追加
+        withdraw: u64,           // 从 Vault 取回的 Token A，Vault 里必须留有余额，全部取回请用 refund
+        expires_at: Option<i64>  // 新的过期时间：只能延后，0 表示取消过期；None 表示不变
+    ) -> Result<()> {
+        // 修改报价无需 refund 再 make，Escrow 地址和租金都保持不变
+        instructions::update::handler(ctx, receive, deposit, withdraw, expires_at)
+    }
+
+    #[instruction(discriminator = 7)]
+    pub fn refund_legacy(
+        ctx: Context<RefundLegacy>,
+        seed: u64 // 旧 Escrow 的种子：用于推导地址
+    ) -> Result<()> {
+        // 加入 expires_at 和 taker 之前创建的 Escrow 无法按新布局反序列化，只能用这条指令退款并关闭
+        instructions::refund_legacy::handler(ctx, seed)
+    }
 }
@@ -1,4 +1,8 @@
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::errors::EscrowError;
+
+pub const MAX_FEE_BPS: u16 = 1_000;
 
 #[derive(InitSpace)]
 #[account(discriminator = 1)]
@@ -7,6 +11,166 @@ pub struct Escrow {
     pub maker: Pubkey,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
+    /// Token B still wanted, reduced by every partial `take`
     pub receive: u64,
+    /// Unix timestamp after which the offer can no longer be taken, 0 means it never expires
+    pub expires_at: i64,
+    /// Only this wallet may `take` when set, for OTC deals agreed off-chain
+    pub taker: Option<Pubkey>,
+    pub bump: u8,
+}
+
+impl Escrow {
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.expires_at != 0 && now >= self.expires_at
+    }
+
+    pub fn allows_taker(&self, taker: &Pubkey) -> bool {
+        self.taker.is_none_or(|designated| designated == *taker)
+    }
+
+    /// # Token A released for `amount_b` out of a vault holding `vault_amount`
+    /// Pro-rata share of what is left, rounded down so the maker never sells below the quoted price
+    pub fn amount_a_for(&self, vault_amount: u64, amount_b: u64) -> u64 {
+        (vault_amount as u128 * amount_b as u128 / self.receive as u128) as u64
+    }
+}
+
+/// `Escrow` as `make` wrote it before `expires_at` and `taker` existed, only read by `refund_legacy`
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct LegacyEscrow {
+    pub seed: u64,
+    pub maker: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub receive: u64,
+    pub bump: u8,
+}
+
+impl LegacyEscrow {
+    /// Account size `make` allocated back then
+    pub const LEN: usize = Self::INIT_SPACE + 8;
+
+    /// # Parse a legacy escrow account, refusing any account in the current layout
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self> {
+        require!(
+            data.len() == Self::LEN && data.starts_with(Escrow::DISCRIMINATOR),
+            EscrowError::InvalidEscrow
+        );
+        Ok(Self::deserialize(&mut &data[Escrow::DISCRIMINATOR.len()..])?)
+    }
+}
+
+/// Program-wide fee settings, a single `[b"config"]` PDA
+#[derive(InitSpace)]
+#[account(discriminator = 2)]
+pub struct Config {
+    pub admin: Pubkey,
+    /// Fee skimmed from the Token B leg of every `take`
+    pub fee_bps: u16,
+    /// Part of the fee paid to the referrer instead of the treasury.
+    /// Any taker can name itself as referrer, so this works as a discount on the fee
+    pub referral_share_bps: u16,
+    /// Wallet whose Token B ATA collects the fee, created by a fee-paying `take` when missing
+    pub treasury: Pubkey,
     pub bump: u8,
 }
+
+impl Config {
+    /// # Split a Token B payment into (maker, treasury, referrer) amounts
+    /// Fees round down, the referrer's share comes out of the protocol fee
+    pub fn split(&self, amount_b: u64, has_referrer: bool) -> (u64, u64, u64) {
+        let fee = (amount_b as u128 * self.fee_bps as u128 / 10_000) as u64;
+        let referrer_fee = if has_referrer {
+            (fee as u128 * self.referral_share_bps as u128 / 10_000) as u64
+        } else {
+            0
+        };
+        (amount_b - fee, fee - referrer_fee, referrer_fee)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn escrow(receive: u64) -> Escrow {
+        Escrow {
+            seed: 0,
+            maker: Pubkey::default(),
+            mint_a: Pubkey::default(),
+            mint_b: Pubkey::default(),
+            receive,
+            expires_at: 0,
+            taker: None,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn full_fill_releases_the_whole_vault() {
+        assert_eq!(escrow(20).amount_a_for(10, 20), 10);
+    }
+
+    #[test]
+    fn partial_fill_is_pro_rata_and_rounds_down() {
+        let escrow = escrow(20);
+        assert_eq!(escrow.amount_a_for(10, 5), 2);
+        assert_eq!(escrow.amount_a_for(10, 1), 0);
+    }
+
+    #[test]
+    fn amount_a_does_not_overflow() {
+        assert_eq!(escrow(u64::MAX).amount_a_for(u64::MAX, u64::MAX), u64::MAX);
+    }
+
+    fn config(fee_bps: u16, referral_share_bps: u16) -> Config {
+        Config { admin: Pubkey::default(), fee_bps, referral_share_bps, treasury: Pubkey::default(), bump: 0 }
+    }
+
+    #[test]
+    fn split_without_fee_pays_the_maker_everything() {
+        assert_eq!(config(0, 5_000).split(1_000, true), (1_000, 0, 0));
+    }
+
+    #[test]
+    fn split_takes_the_referrer_share_out_of_the_fee() {
+        let config = config(100, 2_000);
+        assert_eq!(config.split(10_000, false), (9_900, 100, 0));
+        assert_eq!(config.split(10_000, true), (9_900, 80, 20));
+    }
+
+    #[test]
+    fn split_rounds_fees_down_and_adds_up() {
+        let config = config(30, 3_333);
+        for amount_b in [0, 1, 333, 10_001, u64::MAX] {
+            let (maker, treasury, referrer) = config.split(amount_b, true);
+            assert_eq!(maker as u128 + treasury as u128 + referrer as u128, amount_b as u128);
+        }
+        assert_eq!(config.split(333, true), (333, 0, 0));
+    }
+
+    #[test]
+    fn legacy_escrow_parses_only_the_old_layout() {
+        let legacy = LegacyEscrow {
+            seed: 7,
+            maker: Pubkey::new_unique(),
+            mint_a: Pubkey::new_unique(),
+            mint_b: Pubkey::new_unique(),
+            receive: 20,
+            bump: 254,
+        };
+        let mut data = Escrow::DISCRIMINATOR.to_vec();
+        legacy.serialize(&mut data).unwrap();
+        data.resize(LegacyEscrow::LEN, 0);
+        let parsed = LegacyEscrow::try_from_bytes(&data).unwrap();
+        assert_eq!((parsed.seed, parsed.maker, parsed.bump), (7, legacy.maker, 254));
+
+        let mut current = Escrow::DISCRIMINATOR.to_vec();
+        escrow(20).serialize(&mut current).unwrap();
+        current.resize(Escrow::INIT_SPACE + 8, 0);
+        assert!(LegacyEscrow::try_from_bytes(&current).is_err());
+        data[0] = Config::DISCRIMINATOR[0];
+        assert!(LegacyEscrow::try_from_bytes(&data).is_err());
+    }
+}
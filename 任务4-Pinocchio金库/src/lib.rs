@@ -8,9 +8,10 @@ use pinocchio::{
     nostd_panic_handler,
     program_error::ProgramError,
     pubkey::{find_program_address, Pubkey},
+    sysvars::{rent::Rent, Sysvar},
     ProgramResult,
 };
-use pinocchio_system::instructions::Transfer;
+use pinocchio_system::instructions::{Allocate, Assign, CreateAccount, Transfer};
 
 // 声明入口点
 entrypoint!(process_instruction);
@@ -37,6 +38,12 @@ fn process_instruction(
         Some((Deposit::DISCRIMINATOR, data)) => Deposit::try_from((data, accounts))?.process(),
         // 1 = Withdraw
         Some((Withdraw::DISCRIMINATOR, _)) => Withdraw::try_from(accounts)?.process(),
+        // 2 = ProposeTransfer
+        Some((ProposeTransfer::DISCRIMINATOR, data)) => ProposeTransfer::try_from((data, accounts))?.process(),
+        // 3 = AcceptTransfer
+        Some((AcceptTransfer::DISCRIMINATOR, _)) => AcceptTransfer::try_from(accounts)?.process(),
+        // 4 = CancelTransfer
+        Some((CancelTransfer::DISCRIMINATOR, _)) => CancelTransfer::try_from(accounts)?.process(),
         _ => Err(ProgramError::InvalidInstructionData),
     }
 }
@@ -208,4 +215,274 @@ impl<'a> Withdraw<'a> {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+// ==========================================
+//           所有权转移 逻辑
+// ==========================================
+// Vault 地址由 owner 派生，无法直接改归属，只能把余额搬到新 owner 的 Vault。
+// 分两步：旧 owner 在 [b"transfer", vault] PDA 里记下新 owner，新 owner 签名接受后才搬迁，防止转给写错的地址
+
+pub struct ProposeTransferAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub transfer: &'a AccountInfo,
+    pub bumps: [u8; 1],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for ProposeTransferAccounts<'a> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        // 预期账户：[owner, vault, transfer, system_program]
+        let [owner, vault, transfer, _] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        if !owner.is_signer() {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let (vault_key, _) = find_program_address(&[b"vault", owner.key()], &crate::ID);
+        if vault.key().ne(&vault_key) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let (transfer_key, bump) = find_program_address(&[b"transfer", vault.key()], &crate::ID);
+        if transfer.key().ne(&transfer_key) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        // 已有提议时覆盖本程序创建的账户（用于改正新 owner）；
+        // PDA 地址可预测，别人可能先往里打 lamports，此时它仍归系统程序所有
+        if !transfer.is_owned_by(&crate::ID) && !transfer.is_owned_by(&pinocchio_system::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { owner, vault, transfer, bumps: [bump] })
+    }
+}
+
+pub struct ProposeTransferInstructionData {
+    pub new_owner: Pubkey,
+}
+
+impl<'a> TryFrom<&'a [u8]> for ProposeTransferInstructionData {
+    type Error = ProgramError;
+    fn try_from(data: &'a [u8]) -> Result<Self, Self::Error> {
+        let new_owner: Pubkey = data
+            .try_into()
+            .map_err(|_| ProgramError::InvalidInstructionData)?;
+        Ok(Self { new_owner })
+    }
+}
+
+pub struct ProposeTransfer<'a> {
+    pub accounts: ProposeTransferAccounts<'a>,
+    pub instruction_data: ProposeTransferInstructionData,
+}
+
+impl<'a> TryFrom<(&'a [u8], &'a [AccountInfo])> for ProposeTransfer<'a> {
+    type Error = ProgramError;
+    fn try_from((data, accounts): (&'a [u8], &'a [AccountInfo])) -> Result<Self, Self::Error> {
+        let accounts = ProposeTransferAccounts::try_from(accounts)?;
+        let instruction_data = ProposeTransferInstructionData::try_from(data)?;
+        if accounts.owner.key().eq(&instruction_data.new_owner) {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        Ok(Self {
+            accounts,
+            instruction_data,
+        })
+    }
+}
+
+impl<'a> ProposeTransfer<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &2;
+
+    pub fn process(&mut self) -> ProgramResult {
+        let seeds = [
+            Seed::from(b"transfer"),
+            Seed::from(self.accounts.vault.key().as_ref()),
+            Seed::from(&self.accounts.bumps),
+        ];
+        let signers = [Signer::from(&seeds)];
+        let rent = Rent::get()?.minimum_balance(size_of::<Pubkey>());
+
+        // 第一次提议：创建只存放新 owner 公钥的 transfer PDA
+        if self.accounts.transfer.lamports().eq(&0) {
+            CreateAccount {
+                from: self.accounts.owner,
+                to: self.accounts.transfer,
+                lamports: rent,
+                space: size_of::<Pubkey>() as u64,
+                owner: &crate::ID,
+            }
+                .invoke_signed(&signers)?;
+        } else if !self.accounts.transfer.is_owned_by(&crate::ID) {
+            // 已被预先打款：CreateAccount 会失败，改为补足租金后 Allocate + Assign
+            let lamports = self.accounts.transfer.lamports();
+            if lamports.lt(&rent) {
+                Transfer {
+                    from: self.accounts.owner,
+                    to: self.accounts.transfer,
+                    lamports: rent - lamports,
+                }
+                    .invoke()?;
+            }
+            Allocate {
+                account: self.accounts.transfer,
+                space: size_of::<Pubkey>() as u64,
+            }
+                .invoke_signed(&signers)?;
+            Assign {
+                account: self.accounts.transfer,
+                owner: &crate::ID,
+            }
+                .invoke_signed(&signers)?;
+        }
+
+        self.accounts
+            .transfer
+            .try_borrow_mut_data()?
+            .copy_from_slice(&self.instruction_data.new_owner);
+        Ok(())
+    }
+}
+
+pub struct AcceptTransferAccounts<'a> {
+    pub new_owner: &'a AccountInfo,
+    pub owner: &'a AccountInfo,
+    pub vault: &'a AccountInfo,
+    pub new_vault: &'a AccountInfo,
+    pub transfer: &'a AccountInfo,
+    pub bumps: [u8; 1],
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AcceptTransferAccounts<'a> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        // 预期账户：[new_owner, owner, vault, new_vault, transfer, system_program]
+        let [new_owner, owner, vault, new_vault, transfer, _] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 必须由新 owner 本人签名接受
+        if !new_owner.is_signer() {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let (vault_key, bump) = find_program_address(&[b"vault", owner.key()], &crate::ID);
+        if vault.key().ne(&vault_key) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let (new_vault_key, _) = find_program_address(&[b"vault", new_owner.key()], &crate::ID);
+        if new_vault.key().ne(&new_vault_key) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        // 新 owner 的 Vault 可能已有余额（地址可预测，任何人都能往里打款），搬迁时直接累加
+        if new_vault.owner() != &pinocchio_system::ID {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        // transfer PDA 必须由本程序创建，且记录的正是这位新 owner
+        let (transfer_key, _) = find_program_address(&[b"transfer", vault.key()], &crate::ID);
+        if transfer.key().ne(&transfer_key) || !transfer.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        if transfer.try_borrow_data()?.ne(new_owner.key()) {
+            return Err(ProgramError::InvalidAccountData);
+        }
+
+        Ok(Self { new_owner, owner, vault, new_vault, transfer, bumps: [bump] })
+    }
+}
+
+pub struct AcceptTransfer<'a> {
+    pub accounts: AcceptTransferAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for AcceptTransfer<'a> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = AcceptTransferAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> AcceptTransfer<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &3;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 整笔余额：旧 Vault -> 新 Vault (带旧 Vault 的 PDA 签名)
+        let balance = self.accounts.vault.lamports();
+        if balance.ne(&0) {
+            let seeds = [
+                Seed::from(b"vault"),
+                Seed::from(self.accounts.owner.key().as_ref()),
+                Seed::from(&self.accounts.bumps),
+            ];
+            let signers = [Signer::from(&seeds)];
+
+            Transfer {
+                from: self.accounts.vault,
+                to: self.accounts.new_vault,
+                lamports: balance,
+            }
+                .invoke_signed(&signers)?;
+        }
+
+        // 关闭 transfer PDA，租金退还给旧 owner
+        *self.accounts.owner.try_borrow_mut_lamports()? += self.accounts.transfer.lamports();
+        *self.accounts.transfer.try_borrow_mut_lamports()? = 0;
+        self.accounts.transfer.close()
+    }
+}
+
+pub struct CancelTransferAccounts<'a> {
+    pub owner: &'a AccountInfo,
+    pub transfer: &'a AccountInfo,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelTransferAccounts<'a> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        // 预期账户：[owner, vault, transfer]
+        let [owner, vault, transfer] = accounts else {
+            return Err(ProgramError::NotEnoughAccountKeys);
+        };
+
+        // 只有旧 owner 本人可以撤销提议
+        if !owner.is_signer() {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let (vault_key, _) = find_program_address(&[b"vault", owner.key()], &crate::ID);
+        if vault.key().ne(&vault_key) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+        let (transfer_key, _) = find_program_address(&[b"transfer", vault.key()], &crate::ID);
+        if transfer.key().ne(&transfer_key) || !transfer.is_owned_by(&crate::ID) {
+            return Err(ProgramError::InvalidAccountOwner);
+        }
+
+        Ok(Self { owner, transfer })
+    }
+}
+
+pub struct CancelTransfer<'a> {
+    pub accounts: CancelTransferAccounts<'a>,
+}
+
+impl<'a> TryFrom<&'a [AccountInfo]> for CancelTransfer<'a> {
+    type Error = ProgramError;
+    fn try_from(accounts: &'a [AccountInfo]) -> Result<Self, Self::Error> {
+        let accounts = CancelTransferAccounts::try_from(accounts)?;
+        Ok(Self { accounts })
+    }
+}
+
+impl<'a> CancelTransfer<'a> {
+    pub const DISCRIMINATOR: &'a u8 = &4;
+
+    pub fn process(&mut self) -> ProgramResult {
+        // 关闭 transfer PDA，租金退还给 owner，余额留在原 Vault
+        *self.accounts.owner.try_borrow_mut_lamports()? += self.accounts.transfer.lamports();
+        *self.accounts.transfer.try_borrow_mut_lamports()? = 0;
+        self.accounts.transfer.close()
+    }
+}